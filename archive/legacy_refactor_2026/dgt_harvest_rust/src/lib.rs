@@ -1,12 +1,35 @@
 // DGT Harvest Rust Core - Simplified for PyO3 0.23
 // Minimal viable implementation for Python 3.12
 
+use pyo3::buffer::PyBuffer;
 use pyo3::prelude::*;
-use std::collections::HashMap;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Crate-owned thread pool for parallel methods (None = rayon's global pool)
+static THREAD_POOL: RwLock<Option<Arc<rayon::ThreadPool>>> = RwLock::new(None);
+
+/// Relative luma gap between even and odd rows that counts as scanlines
+const SCANLINE_MIN_CONTRAST: f64 = 0.15;
+
+/// `is_tileable_background` limits: near-opaque frame, mean seam difference
+/// (RGBA, 0-255) and share of the frame border covered by content
+const TILE_MAX_TRANSPARENCY: f64 = 0.05;
+const TILE_MAX_SEAM_DIFFERENCE: f64 = 24.0;
+const TILE_MIN_BORDER_COVERAGE: f64 = 0.9;
+
+/// `asset_class` limits: icons are small and near-square, scenes are large
+/// and (almost) fully opaque
+const ICON_MAX_DIM: u32 = 32;
+const ICON_MAX_ASPECT: f64 = 1.5;
+const SCENE_MIN_DIM: u32 = 256;
+const SCENE_MIN_FILL: f64 = 0.95;
 
 /// Material DNA - Complete sprite analysis
 #[pyclass]
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 struct MaterialDNA {
     #[pyo3(get)]
     alpha_bounding_box: (u32, u32, u32, u32),
@@ -31,38 +54,2729 @@ struct MaterialDNA {
     
     #[pyo3(get)]
     transparency_ratio: f64,
+    
+    #[pyo3(get)]
+    is_empty: bool, // No opaque pixels; alpha_bounding_box is (0, 0, 0, 0)
+}
+
+#[pymethods]
+impl MaterialDNA {
+    /// Scalar features as a flat vector for classifier training
+    ///
+    /// The order is stable:
+    /// `[confidence, edge_density, transparency_ratio, wood, stone, grass,
+    /// water, other, is_object, is_empty]`, where the material entries are
+    /// `color_profile` ratios (0.0 when absent) and bools are 0.0/1.0.
+    fn as_feature_vector(&self) -> Vec<f64> {
+        let ratio = |category: &str| self.color_profile.get(category).copied().unwrap_or(0.0);
+
+        vec![
+            self.confidence,
+            self.edge_density,
+            self.transparency_ratio,
+            ratio("wood"),
+            ratio("stone"),
+            ratio("grass"),
+            ratio("water"),
+            ratio("other"),
+            if self.is_object { 1.0 } else { 0.0 },
+            if self.is_empty { 1.0 } else { 0.0 },
+        ]
+    }
+
+    /// Compact binary encoding (bincode) for local result caches
+    ///
+    /// The format is tied to this crate version's field layout and is not
+    /// stable across releases - use it for caches, not long-term storage.
+    fn to_bytes(&self) -> PyResult<Vec<u8>> {
+        bincode::serialize(self).map_err(|e| pyo3::exceptions::PyValueError::new_err(
+            format!("Failed to serialize MaterialDNA: {}", e)
+        ))
+    }
+
+    /// Decode a MaterialDNA produced by `to_bytes` with the same crate version
+    #[staticmethod]
+    fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        bincode::deserialize(data).map_err(|e| pyo3::exceptions::PyValueError::new_err(
+            format!("Failed to deserialize MaterialDNA: {}", e)
+        ))
+    }
 }
 
 /// High-performance Material Triage Engine
 #[pyclass]
 struct MaterialTriageEngine {
     edge_threshold: f64,
+    analysis_max_dim: Option<u32>,
+    weight_by_alpha: bool, // Color ratios weighted by alpha/255 instead of pixel count
+    ignore_outline: bool, // Exclude dark outline pixels from the color profile
+    outline_luma_max: u8, // Luminance at or below which a pixel counts as outline
+    linearize: bool, // Classify in linear light instead of gamma-encoded sRGB
+    luma_weights: (f32, f32, f32), // Grayscale weights, normalized to sum to 1
+    reference_palette: Vec<(String, (u8, u8, u8))>, // Named colors for classify_by_palette
+    cache: Mutex<Option<AnalysisCache>>, // Opt-in via enable_cache
+}
+
+/// Cache key: (FNV-1a content hash, width, height)
+type CacheKey = (u64, u32, u32);
+
+/// A cached result plus what is needed to verify and age it
+struct CacheEntry {
+    dna: MaterialDNA,
+    check: u64,      // Independent SipHash of the bytes, guards against FNV collisions
+    generation: u64, // Last use, larger is more recent
+}
+
+/// Bounded LRU cache of analysis results
+///
+/// Every use stamps the entry with a new generation and `recency` orders the
+/// live generations, so a hit or an eviction never scans the whole cache.
+struct AnalysisCache {
+    capacity: usize,
+    generation: u64,
+    entries: HashMap<CacheKey, CacheEntry>,
+    recency: BTreeMap<u64, CacheKey>, // Least recently used first
+    hits: u64,
+    misses: u64,
+}
+
+impl AnalysisCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            generation: 0,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Look up a result, treating a key match with a different check hash as a miss
+    fn get(&mut self, key: &CacheKey, check: u64) -> Option<MaterialDNA> {
+        self.generation += 1;
+        match self.entries.get_mut(key) {
+            Some(entry) if entry.check == check => {
+                self.recency.remove(&entry.generation);
+                entry.generation = self.generation;
+                self.recency.insert(self.generation, *key);
+                self.hits += 1;
+                Some(entry.dna.clone())
+            }
+            _ => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    fn insert(&mut self, key: CacheKey, check: u64, dna: MaterialDNA) {
+        self.generation += 1;
+        let entry = CacheEntry { dna, check, generation: self.generation };
+        if let Some(previous) = self.entries.insert(key, entry) {
+            self.recency.remove(&previous.generation);
+        }
+        self.recency.insert(self.generation, key);
+
+        while self.entries.len() > self.capacity {
+            match self.recency.pop_first() {
+                Some((_, evicted)) => { self.entries.remove(&evicted); }
+                None => break,
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
 }
 
 #[pymethods]
 impl MaterialTriageEngine {
     #[new]
-    fn new() -> Self {
-        Self {
+    #[pyo3(signature = (
+        analysis_max_dim = None,
+        weight_by_alpha = false,
+        ignore_outline = false,
+        outline_luma_max = 40,
+        linearize = false,
+        luma_weights = (0.299, 0.587, 0.114),
+    ))]
+    fn new(
+        analysis_max_dim: Option<u32>,
+        weight_by_alpha: bool,
+        ignore_outline: bool,
+        outline_luma_max: u8,
+        linearize: bool,
+        luma_weights: (f32, f32, f32),
+    ) -> PyResult<Self> {
+        if analysis_max_dim == Some(0) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "analysis_max_dim must be greater than 0"
+            ));
+        }
+
+        let (wr, wg, wb) = luma_weights;
+        let weight_sum = wr + wg + wb;
+        if wr < 0.0 || wg < 0.0 || wb < 0.0 || !weight_sum.is_finite() || weight_sum <= 0.0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "luma_weights must be non-negative with a positive sum"
+            ));
+        }
+
+        Ok(Self {
             edge_threshold: 0.2,
+            analysis_max_dim,
+            weight_by_alpha,
+            ignore_outline,
+            outline_luma_max,
+            linearize,
+            // Normalize so grayscale stays within 0-255
+            luma_weights: (wr / weight_sum, wg / weight_sum, wb / weight_sum),
+            reference_palette: Vec::new(),
+            cache: Mutex::new(None),
+        })
+    }
+
+    /// Complete Material Triage Analysis
+    ///
+    /// With `analysis_max_dim` set, sprites whose longest side exceeds the cap
+    /// are box-downsampled first. Ratios and colors then become slight
+    /// approximations, while the bounding box is still measured on the
+    /// full-resolution alpha, so faint pixels that average away are kept.
+    fn analyze_sprite(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<MaterialDNA> {
+        validate_rgba(pixels, width, height)?;
+
+        Ok(self.analyze_cached(pixels, width, height))
+    }
+
+    /// Material Triage Analysis for any buffer-protocol object
+    ///
+    /// Convenience API accepting a C-contiguous uint8 buffer (bytes, bytearray,
+    /// memoryview or a contiguous numpy array). The pixels are copied once into
+    /// an owned buffer with a single bulk copy, which costs the same as calling
+    /// `bytes(obj)` but keeps native code that released the GIL from changing
+    /// them mid-analysis.
+    fn analyze_sprite_buffer(&self, py: Python<'_>, buffer: PyBuffer<u8>, width: u32, height: u32) -> PyResult<MaterialDNA> {
+        if !buffer.is_c_contiguous() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Pixel buffer must be C-contiguous"
+            ));
+        }
+        let pixels = buffer.to_vec(py)?;
+        validate_rgba(&pixels, width, height)?;
+
+        Ok(self.analyze_cached(&pixels, width, height))
+    }
+
+    /// Enable an LRU cache of analysis results holding up to `capacity` sprites
+    ///
+    /// Results are keyed by an FNV-1a hash of the pixel bytes plus the
+    /// dimensions, and a hit must also match an independent SipHash of the
+    /// bytes, so a collision on one hash is treated as a miss rather than
+    /// returning another sprite's result. Analysis is deterministic, so
+    /// identical bytes always produce identical results and a cache hit skips
+    /// the scan entirely. Re-enabling replaces any existing cache.
+    fn enable_cache(&mut self, capacity: usize) -> PyResult<()> {
+        if capacity == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Cache capacity must be greater than 0"
+            ));
+        }
+
+        *self.cache.get_mut().unwrap_or_else(|e| e.into_inner()) = Some(AnalysisCache::new(capacity));
+        Ok(())
+    }
+
+    /// Drop all cached analysis results (the cache stays enabled)
+    fn clear_cache(&mut self) {
+        if let Some(cache) = self.cache.get_mut().unwrap_or_else(|e| e.into_inner()).as_mut() {
+            cache.clear();
+        }
+    }
+
+    /// Cache counters as (hits, misses, entries), or None when caching is off
+    ///
+    /// Hits and misses accumulate since the cache was enabled; clear_cache only
+    /// drops the entries.
+    fn cache_stats(&self) -> Option<(u64, u64, usize)> {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner()).as_ref()
+            .map(|cache| (cache.hits, cache.misses, cache.entries.len()))
+    }
+
+    /// Get Alpha-Bounding Box (ABB)
+    fn get_alpha_bounding_box(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<(u32, u32, u32, u32)> {
+        validate_rgba(pixels, width, height)?;
+
+        Ok(self.calculate_alpha_bounding_box(pixels, width, height))
+    }
+
+    /// Bucket fill from a seed point using a scanline flood fill
+    ///
+    /// Pixels are connected horizontally and vertically (4-connectivity) and
+    /// match when every RGBA channel is within `tolerance` of the seed color.
+    /// Returns a new buffer; the input is left untouched.
+    #[allow(clippy::too_many_arguments)]
+    fn flood_fill(
+        &self,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        x: u32,
+        y: u32,
+        fill: (u8, u8, u8, u8),
+        tolerance: u8,
+    ) -> PyResult<Vec<u8>> {
+        validate_rgba(pixels, width, height)?;
+
+        if x >= width || y >= height {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Seed point is outside the image bounds"
+            ));
+        }
+
+        Ok(self.scanline_fill(pixels, width, height, x, y, fill, tolerance))
+    }
+
+    /// Detect a solid color swatch - every opaque pixel within `tolerance` of the first
+    ///
+    /// A fully transparent sprite has no fill color and is reported as not solid.
+    fn is_solid_fill(&self, pixels: &[u8], width: u32, height: u32, tolerance: u8) -> PyResult<bool> {
+        validate_rgba(pixels, width, height)?;
+
+        let mut reference: Option<&[u8]> = None;
+
+        for chunk in pixels.chunks_exact(4) {
+            if chunk[3] == 0 {
+                continue;
+            }
+
+            match reference {
+                None => reference = Some(&chunk[..3]),
+                Some(first) => {
+                    let differs = first.iter().zip(chunk[..3].iter()).any(|(&a, &b)| a.abs_diff(b) > tolerance);
+                    if differs {
+                        return Ok(false);
+                    }
+                }
+            }
+        }
+
+        Ok(reference.is_some())
+    }
+
+    /// Find connected opaque regions, returning (x, y, width, height) per component
+    ///
+    /// `connectivity` is 4 (edge neighbours only) or 8 (edges and corners). With
+    /// 8-connectivity, runs that only touch diagonally - such as a 1px diagonal
+    /// line - merge into a single component. Components smaller than `min_area`
+    /// pixels are skipped. Results are ordered by first pixel in row-major order.
+    #[pyo3(signature = (pixels, width, height, min_area = 1, connectivity = 4))]
+    fn find_components(
+        &self,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        min_area: u32,
+        connectivity: u8,
+    ) -> PyResult<Vec<(u32, u32, u32, u32)>> {
+        validate_rgba(pixels, width, height)?;
+        validate_connectivity(connectivity)?;
+
+        let (labels, areas) = self.label_components(pixels, width, height, connectivity);
+
+        // min_x, min_y, max_x, max_y per label
+        let mut bounds = vec![(u32::MAX, u32::MAX, 0u32, 0u32); areas.len()];
+        for (i, &label) in labels.iter().enumerate() {
+            if label == 0 {
+                continue;
+            }
+            let x = (i as u32) % width;
+            let y = (i as u32) / width;
+            let b = &mut bounds[label as usize - 1];
+            b.0 = b.0.min(x);
+            b.1 = b.1.min(y);
+            b.2 = b.2.max(x);
+            b.3 = b.3.max(y);
+        }
+
+        Ok(bounds
+            .into_iter()
+            .zip(areas)
+            .filter(|&(_, area)| area >= min_area)
+            .map(|((min_x, min_y, max_x, max_y), _)| (min_x, min_y, max_x - min_x + 1, max_y - min_y + 1))
+            .collect())
+    }
+
+    /// Remove stray specks - components smaller than `min_area` become transparent
+    ///
+    /// `connectivity` follows the same 4/8 rules as `find_components`.
+    #[pyo3(signature = (pixels, width, height, min_area, connectivity = 4))]
+    fn remove_specks(
+        &self,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        min_area: u32,
+        connectivity: u8,
+    ) -> PyResult<Vec<u8>> {
+        validate_rgba(pixels, width, height)?;
+        validate_connectivity(connectivity)?;
+
+        let (labels, areas) = self.label_components(pixels, width, height, connectivity);
+
+        let mut output = pixels.to_vec();
+        for (i, &label) in labels.iter().enumerate() {
+            if label != 0 && areas[label as usize - 1] < min_area {
+                output[i * 4..i * 4 + 4].fill(0);
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Generate a UI-quality thumbnail whose longest side is `max_dim`
+    ///
+    /// Uses area-averaging (box filter) weighted by alpha, so transparent
+    /// surroundings don't darken the edges. Aspect ratio is preserved and each
+    /// side is at least 1 pixel. Returns (pixels, width, height).
+    fn thumbnail(&self, pixels: &[u8], width: u32, height: u32, max_dim: u32) -> PyResult<(Vec<u8>, u32, u32)> {
+        validate_rgba(pixels, width, height)?;
+
+        if max_dim == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "max_dim must be greater than 0"
+            ));
+        }
+        if width == 0 || height == 0 {
+            return Ok((Vec::new(), width, height));
+        }
+
+        let scale = max_dim as f64 / width.max(height) as f64;
+        let target_width = ((width as f64 * scale).round() as u32).max(1);
+        let target_height = ((height as f64 * scale).round() as u32).max(1);
+        rgba_len(target_width, target_height)?;
+
+        let resized = self.box_resample(pixels, width, height, target_width, target_height);
+        Ok((resized, target_width, target_height))
+    }
+
+    /// Count distinct alpha values - 1 or 2 (just 0 and 255) means a hard mask
+    fn alpha_levels(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<u32> {
+        validate_rgba(pixels, width, height)?;
+
+        let mut seen = [false; 256];
+        for chunk in pixels.chunks_exact(4) {
+            seen[chunk[3] as usize] = true;
+        }
+
+        Ok(seen.iter().filter(|&&s| s).count() as u32)
+    }
+
+    /// Pack sprites into a single atlas using a shelf packer
+    ///
+    /// Sprites are placed tallest-first in left-to-right rows no wider than
+    /// `max_width`, with `padding` transparent pixels between neighbours and
+    /// rows. Returns (atlas pixels, atlas width, atlas height, top-left of each
+    /// input sprite in input order).
+    #[allow(clippy::type_complexity)]
+    fn pack_atlas(
+        &self,
+        sprites: Vec<(Vec<u8>, u32, u32)>,
+        max_width: u32,
+        padding: u32,
+    ) -> PyResult<(Vec<u8>, u32, u32, Vec<(u32, u32)>)> {
+        for (pixels, width, height) in &sprites {
+            validate_rgba(pixels, *width, *height)?;
+            if *width > max_width {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    format!("Sprite width {} exceeds atlas max_width {}", width, max_width)
+                ));
+            }
+        }
+
+        // Tallest first keeps shelves tight
+        let mut order: Vec<usize> = (0..sprites.len()).collect();
+        order.sort_by(|&a, &b| sprites[b].2.cmp(&sprites[a].2));
+
+        let mut placements = vec![(0u32, 0u32); sprites.len()];
+        let mut cursor_x = 0u32;
+        let mut shelf_y = 0u32;
+        let mut shelf_height = 0u32;
+        let mut atlas_width = 0u32;
+
+        // Saturating steps let an oversized layout fail the size check below
+        for &i in &order {
+            let (_, width, height) = sprites[i];
+            if cursor_x > 0 && cursor_x.saturating_add(width) > max_width {
+                shelf_y = shelf_y.saturating_add(shelf_height).saturating_add(padding);
+                cursor_x = 0;
+                shelf_height = 0;
+            }
+
+            placements[i] = (cursor_x, shelf_y);
+            atlas_width = atlas_width.max(cursor_x.saturating_add(width));
+            shelf_height = shelf_height.max(height);
+            cursor_x = cursor_x.saturating_add(width).saturating_add(padding);
+        }
+        let atlas_height = shelf_y.saturating_add(shelf_height);
+
+        let mut atlas = vec![0u8; rgba_len(atlas_width, atlas_height)?];
+        for ((pixels, width, height), &(px, py)) in sprites.iter().zip(&placements) {
+            let row_bytes = (*width * 4) as usize;
+            for y in 0..*height {
+                let src = (y * width * 4) as usize;
+                let dst = (((py + y) * atlas_width + px) * 4) as usize;
+                atlas[dst..dst + row_bytes].copy_from_slice(&pixels[src..src + row_bytes]);
+            }
+        }
+
+        Ok((atlas, atlas_width, atlas_height, placements))
+    }
+
+    /// Brightness/contrast adjustment: out = (in - 128) * contrast + 128 + brightness * 255
+    ///
+    /// `brightness` is in [-1, 1] and `contrast` of 1.0 leaves the image
+    /// unchanged. Applied per RGB channel with clamping; alpha is untouched.
+    fn adjust(&self, pixels: &[u8], width: u32, height: u32, brightness: f32, contrast: f32) -> PyResult<Vec<u8>> {
+        validate_rgba(pixels, width, height)?;
+
+        // Every channel maps through the same curve, so precompute it once
+        let mut lut = [0u8; 256];
+        for (value, out) in lut.iter_mut().enumerate() {
+            let adjusted = (value as f32 - 128.0) * contrast + 128.0 + brightness * 255.0;
+            *out = adjusted.round().clamp(0.0, 255.0) as u8;
+        }
+
+        let mut output = pixels.to_vec();
+        for chunk in output.chunks_exact_mut(4) {
+            for channel in &mut chunk[..3] {
+                *channel = lut[*channel as usize];
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Rough silhouette classifier: "circle", "rectangle" or "irregular"
+    ///
+    /// Compares the fraction of the bounding box covered by opaque pixels
+    /// against reference shapes: a filled box covers ~1.0 and an inscribed
+    /// ellipse ~π/4 (0.785). Anything sparser is irregular, as is an empty sprite.
+    fn silhouette_shape(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<String> {
+        validate_rgba(pixels, width, height)?;
+
+        let (_, _, bbox_width, bbox_height) = self.calculate_alpha_bounding_box(pixels, width, height);
+        let box_area = bbox_width as u64 * bbox_height as u64;
+        if box_area == 0 {
+            return Ok("irregular".to_string());
+        }
+
+        let opaque = pixels.chunks_exact(4).filter(|chunk| chunk[3] > 0).count();
+        let fill_ratio = opaque as f64 / box_area as f64;
+
+        let shape = if fill_ratio >= 0.9 {
+            "rectangle"
+        } else if (fill_ratio - std::f64::consts::FRAC_PI_4).abs() <= 0.08 {
+            "circle"
+        } else {
+            "irregular"
+        };
+
+        Ok(shape.to_string())
+    }
+
+    /// Material Triage Analysis for 16-bit-per-channel RGBA (e.g. RGBA16 PNGs)
+    ///
+    /// Samples are rounded to 8 bits and classified against the 8-bit
+    /// thresholds, so a sample within half an 8-bit step of a threshold may
+    /// land on either side (e.g. R=25600 rounds to 100 and passes `r >= 100`).
+    /// `analysis_max_dim` applies as in `analyze_sprite`. Any non-zero 16-bit
+    /// alpha still counts as opaque, and the dominant color is averaged at full
+    /// 16-bit precision before rounding.
+    fn analyze_sprite_u16(&self, pixels: Vec<u16>, width: u32, height: u32) -> PyResult<MaterialDNA> {
+        if pixels.len() != rgba_len(width, height)? {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Pixel data length doesn't match dimensions"
+            ));
+        }
+
+        let to_u8 = |v: u16| ((v as u32 * 255 + 32767) / 65535) as u8;
+        let scaled: Vec<u8> = pixels
+            .chunks_exact(4)
+            .flat_map(|chunk| {
+                // Keep faint 16-bit alpha from rounding down to transparent
+                let alpha = if chunk[3] > 0 { to_u8(chunk[3]).max(1) } else { 0 };
+                [to_u8(chunk[0]), to_u8(chunk[1]), to_u8(chunk[2]), alpha]
+            })
+            .collect();
+
+        let mut dna = self.analyze_capped(&scaled, width, height);
+
+        let mut sums = [0u64; 3];
+        let mut count = 0u64;
+        for chunk in pixels.chunks_exact(4).filter(|chunk| chunk[3] > 0) {
+            for c in 0..3 {
+                sums[c] += chunk[c] as u64;
+            }
+            count += 1;
+        }
+        if count > 0 {
+            let mean = |sum: u64| to_u8(((sum + count / 2) / count) as u16);
+            dna.dominant_color = (mean(sums[0]), mean(sums[1]), mean(sums[2]));
+        }
+
+        Ok(dna)
+    }
+
+    /// Count of non-transparent pixels (alpha > 0)
+    fn opaque_pixel_count(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<u32> {
+        validate_rgba(pixels, width, height)?;
+
+        Ok(pixels.chunks_exact(4).filter(|chunk| chunk[3] > 0).count() as u32)
+    }
+
+    /// Generate a normal map treating grayscale luminance as a heightfield
+    ///
+    /// Gradients come from a 3x3 Sobel operator (edges clamp to the border) and
+    /// are scaled by `strength`. Normals are packed as (0.5 * n + 0.5) * 255 in
+    /// image space (Y down, DirectX-style) with the original alpha kept.
+    /// Transparent pixels encode a flat up-normal (128, 128, 255, 0).
+    fn generate_normal_map(&self, pixels: &[u8], width: u32, height: u32, strength: f32) -> PyResult<Vec<u8>> {
+        validate_rgba(pixels, width, height)?;
+
+        let w = width as usize;
+        let h = height as usize;
+        let heights: Vec<f32> = self.grayscale(pixels).iter().map(|&l| l / 255.0).collect();
+        let at = |x: isize, y: isize| -> f32 {
+            let cx = x.clamp(0, w as isize - 1) as usize;
+            let cy = y.clamp(0, h as isize - 1) as usize;
+            heights[cy * w + cx]
+        };
+
+        let mut output = vec![0u8; pixels.len()];
+        for y in 0..h {
+            for x in 0..w {
+                let idx = (y * w + x) * 4;
+                let alpha = pixels[idx + 3];
+                if alpha == 0 {
+                    output[idx..idx + 4].copy_from_slice(&[128, 128, 255, 0]);
+                    continue;
+                }
+
+                let (xi, yi) = (x as isize, y as isize);
+                let sobel_x = -at(xi - 1, yi - 1) + at(xi + 1, yi - 1)
+                    - 2.0 * at(xi - 1, yi) + 2.0 * at(xi + 1, yi)
+                    - at(xi - 1, yi + 1) + at(xi + 1, yi + 1);
+                let sobel_y = -at(xi - 1, yi - 1) - 2.0 * at(xi, yi - 1) - at(xi + 1, yi - 1)
+                    + at(xi - 1, yi + 1) + 2.0 * at(xi, yi + 1) + at(xi + 1, yi + 1);
+
+                let nx = -sobel_x * strength;
+                let ny = -sobel_y * strength;
+                let length = (nx * nx + ny * ny + 1.0).sqrt();
+                let pack = |n: f32| ((n / length * 0.5 + 0.5) * 255.0).round().clamp(0.0, 255.0) as u8;
+
+                output[idx] = pack(nx);
+                output[idx + 1] = pack(ny);
+                output[idx + 2] = pack(1.0);
+                output[idx + 3] = alpha;
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Warm/cool bias: mean (R - B) over opaque pixels, normalized to [-1, 1]
+    ///
+    /// Positive is warm, negative is cool. Fully transparent sprites return 0.0.
+    fn color_temperature(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<f64> {
+        validate_rgba(pixels, width, height)?;
+
+        let mut total = 0i64;
+        let mut count = 0u64;
+        for chunk in pixels.chunks_exact(4).filter(|chunk| chunk[3] > 0) {
+            total += chunk[0] as i64 - chunk[2] as i64;
+            count += 1;
+        }
+
+        if count > 0 {
+            Ok(total as f64 / count as f64 / 255.0)
+        } else {
+            Ok(0.0)
+        }
+    }
+
+    /// Square crop around the content, returning (pixels, side)
+    ///
+    /// The side is the larger content box dimension. The square is centered on
+    /// the opaque-pixel centroid, shifted as needed so the whole content box
+    /// stays inside it, then clamped to the image. Where the square is larger
+    /// than the image it is padded with transparent pixels. An empty sprite
+    /// returns an empty buffer with side 0.
+    fn square_crop(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<(Vec<u8>, u32)> {
+        validate_rgba(pixels, width, height)?;
+
+        let (bx, by, bw, bh) = self.calculate_alpha_bounding_box(pixels, width, height);
+        let Some((cx, cy)) = self.content_centroid(pixels, width) else {
+            return Ok((Vec::new(), 0));
+        };
+        let side = bw.max(bh);
+
+        let place = |center: f64, box_min: u32, box_len: u32, image_len: u32| -> i64 {
+            let side = side as i64;
+            let ideal = (center + 0.5 - side as f64 / 2.0).floor() as i64;
+            let origin = ideal.clamp((box_min + box_len) as i64 - side, box_min as i64);
+            if side <= image_len as i64 {
+                origin.clamp(0, image_len as i64 - side)
+            } else {
+                (image_len as i64 - side) / 2
+            }
+        };
+        let origin_x = place(cx, bx, bw, width);
+        let origin_y = place(cy, by, bh, height);
+
+        Ok((self.crop_padded(pixels, width, height, origin_x, origin_y, side, side), side))
+    }
+
+    /// Bounding box (x, y, width, height) of opaque pixels classified as `category`
+    ///
+    /// Categories are those produced by `classify_color` ("wood", "stone",
+    /// "grass", "water", "other"). Returns (0, 0, 0, 0) when nothing matches.
+    fn category_bounds(
+        &self,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        category: &str,
+    ) -> PyResult<(u32, u32, u32, u32)> {
+        validate_rgba(pixels, width, height)?;
+
+        let mut min_x = width;
+        let mut min_y = height;
+        let mut max_x = 0u32;
+        let mut max_y = 0u32;
+        let mut found = false;
+
+        for (i, chunk) in pixels.chunks_exact(4).enumerate() {
+            if chunk[3] == 0 || self.classify_color(chunk[0], chunk[1], chunk[2]) != category {
+                continue;
+            }
+
+            let x = (i as u32) % width;
+            let y = (i as u32) / width;
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+            found = true;
+        }
+
+        if !found {
+            return Ok((0, 0, 0, 0));
+        }
+
+        Ok((min_x, min_y, max_x - min_x + 1, max_y - min_y + 1))
+    }
+
+    /// Count interior holes - transparent regions fully enclosed by the silhouette
+    ///
+    /// Transparent pixels reachable from the image border are background; the
+    /// remaining transparent regions (4-connected) are holes. A solid blob
+    /// returns 0 and a donut returns 1.
+    fn count_holes(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<u32> {
+        validate_rgba(pixels, width, height)?;
+
+        let holes = self.interior_hole_mask(pixels, width, height);
+        let (_, areas) = self.label_mask(&holes, width, height, 4);
+
+        Ok(areas.len() as u32)
+    }
+
+    /// Run-length encode the alpha mask as (run_length, is_opaque) pairs
+    ///
+    /// Scans row-major across the whole image (runs continue across row
+    /// boundaries); a pixel is opaque when alpha > `threshold`.
+    fn rle_alpha_mask(&self, pixels: &[u8], width: u32, height: u32, threshold: u8) -> PyResult<Vec<(u32, bool)>> {
+        validate_rgba(pixels, width, height)?;
+
+        let mut runs: Vec<(u32, bool)> = Vec::new();
+        for chunk in pixels.chunks_exact(4) {
+            let opaque = chunk[3] > threshold;
+            match runs.last_mut() {
+                Some((length, state)) if *state == opaque => *length += 1,
+                _ => runs.push((1, opaque)),
+            }
+        }
+
+        Ok(runs)
+    }
+
+    /// Manhattan distance from each pixel to the nearest transparent pixel
+    ///
+    /// Two-pass chamfer transform over the alpha mask. Transparent pixels are 0
+    /// and the area outside the image counts as transparent, so opaque pixels
+    /// on the border are 1. Output is `width * height` row-major u16 values,
+    /// saturating at 65535.
+    fn distance_transform(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<Vec<u16>> {
+        validate_rgba(pixels, width, height)?;
+
+        let w = width as usize;
+        let h = height as usize;
+        let mut distances: Vec<u16> = pixels
+            .chunks_exact(4)
+            .map(|chunk| if chunk[3] > 0 { u16::MAX } else { 0 })
+            .collect();
+
+        // Forward pass: up and left neighbours
+        for y in 0..h {
+            for x in 0..w {
+                let idx = y * w + x;
+                if distances[idx] == 0 {
+                    continue;
+                }
+                let up = if y > 0 { distances[idx - w] } else { 0 };
+                let left = if x > 0 { distances[idx - 1] } else { 0 };
+                distances[idx] = distances[idx].min(up.saturating_add(1)).min(left.saturating_add(1));
+            }
+        }
+
+        // Backward pass: down and right neighbours
+        for y in (0..h).rev() {
+            for x in (0..w).rev() {
+                let idx = y * w + x;
+                if distances[idx] == 0 {
+                    continue;
+                }
+                let down = if y + 1 < h { distances[idx + w] } else { 0 };
+                let right = if x + 1 < w { distances[idx + 1] } else { 0 };
+                distances[idx] = distances[idx].min(down.saturating_add(1)).min(right.saturating_add(1));
+            }
+        }
+
+        Ok(distances)
+    }
+
+    /// Check the sprite uses at most `max_colors` distinct opaque RGB colors
+    ///
+    /// Alpha is ignored when comparing colors. Stops scanning as soon as the
+    /// limit is exceeded.
+    fn fits_palette(&self, pixels: &[u8], width: u32, height: u32, max_colors: usize) -> PyResult<bool> {
+        validate_rgba(pixels, width, height)?;
+
+        let mut colors = HashSet::new();
+        for chunk in pixels.chunks_exact(4).filter(|chunk| chunk[3] > 0) {
+            let packed = u32::from_be_bytes([0, chunk[0], chunk[1], chunk[2]]);
+            if colors.insert(packed) && colors.len() > max_colors {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Rotate by an arbitrary angle (degrees, counter-clockwise on screen)
+    ///
+    /// Uses inverse mapping with bilinear sampling around the image center.
+    /// The canvas grows to fit the rotated bounds and uncovered areas are
+    /// transparent. Interpolation is alpha-weighted so edges don't pick up dark
+    /// fringes. Returns (pixels, width, height).
+    fn rotate(&self, pixels: &[u8], width: u32, height: u32, degrees: f32) -> PyResult<(Vec<u8>, u32, u32)> {
+        validate_rgba(pixels, width, height)?;
+
+        self.rotate_bilinear(pixels, width, height, degrees as f64)
+    }
+
+    /// Local shading metric: mean absolute luminance difference between opaque neighbours
+    ///
+    /// Averaged over every horizontally or vertically adjacent pair of opaque
+    /// pixels, in luminance units (0-255). Flat fills score near 0, smooth
+    /// shading moderately and noisy detail high. Returns 0.0 when no pairs exist.
+    fn shading_score(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<f64> {
+        validate_rgba(pixels, width, height)?;
+
+        let w = width as usize;
+        let gray = self.grayscale(pixels);
+        let opaque = |i: usize| pixels[i * 4 + 3] > 0;
+
+        let mut total = 0.0f64;
+        let mut pairs = 0u64;
+        for i in (0..gray.len()).filter(|&i| opaque(i)) {
+            // Right and down neighbours visit each pair exactly once
+            if (i % w) + 1 < w && opaque(i + 1) {
+                total += (gray[i] - gray[i + 1]).abs() as f64;
+                pairs += 1;
+            }
+            if i + w < gray.len() && opaque(i + w) {
+                total += (gray[i] - gray[i + w]).abs() as f64;
+                pairs += 1;
+            }
+        }
+
+        if pairs > 0 {
+            Ok(total / pairs as f64)
+        } else {
+            Ok(0.0)
+        }
+    }
+
+    /// Shannon entropy (bits) of the luminance histogram over opaque pixels
+    ///
+    /// A flat fill is 0.0 and a texture using all 256 gray levels evenly
+    /// approaches 8.0. Empty sprites return 0.0.
+    fn entropy(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<f64> {
+        validate_rgba(pixels, width, height)?;
+
+        let mut histogram = [0u64; 256];
+        let mut count = 0u64;
+        for chunk in pixels.chunks_exact(4).filter(|chunk| chunk[3] > 0) {
+            let luma = self.luminance(chunk[0], chunk[1], chunk[2]).round().min(255.0) as usize;
+            histogram[luma] += 1;
+            count += 1;
+        }
+
+        if count == 0 {
+            return Ok(0.0);
+        }
+
+        let entropy = histogram
+            .iter()
+            .filter(|&&n| n > 0)
+            .map(|&n| {
+                let p = n as f64 / count as f64;
+                p * (1.0 / p).log2()
+            })
+            .sum();
+
+        Ok(entropy)
+    }
+
+    /// Single-channel collision mask: 255 where alpha > `threshold`, else 0
+    ///
+    /// One byte per pixel, row-major; bit-packing is left to the caller.
+    fn binarize_alpha(&self, pixels: &[u8], width: u32, height: u32, threshold: u8) -> PyResult<Vec<u8>> {
+        validate_rgba(pixels, width, height)?;
+
+        Ok(pixels
+            .chunks_exact(4)
+            .map(|chunk| if chunk[3] > threshold { 255 } else { 0 })
+            .collect())
+    }
+
+    /// Which image edges are saturated with content, as (top, right, bottom, left)
+    ///
+    /// An edge counts when at least `fraction` of its row/column is opaque,
+    /// which suggests the artwork continues beyond the frame. Empty sprites
+    /// return all false.
+    fn saturated_edges(
+        &self,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        fraction: f64,
+    ) -> PyResult<(bool, bool, bool, bool)> {
+        validate_rgba(pixels, width, height)?;
+
+        if !(0.0..=1.0).contains(&fraction) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "fraction must be between 0.0 and 1.0"
+            ));
+        }
+        if !pixels.chunks_exact(4).any(|chunk| chunk[3] > 0) {
+            return Ok((false, false, false, false));
+        }
+
+        let opaque = |x: u32, y: u32| pixels[((y * width + x) * 4 + 3) as usize] > 0;
+        let saturated = |count: usize, length: u32| count as f64 >= fraction * length as f64;
+
+        let top = (0..width).filter(|&x| opaque(x, 0)).count();
+        let bottom = (0..width).filter(|&x| opaque(x, height - 1)).count();
+        let left = (0..height).filter(|&y| opaque(0, y)).count();
+        let right = (0..height).filter(|&y| opaque(width - 1, y)).count();
+
+        Ok((
+            saturated(top, width),
+            saturated(right, height),
+            saturated(bottom, width),
+            saturated(left, height),
+        ))
+    }
+
+    /// Exact-match palette remap of opaque pixels via an old -> new RGB table
+    ///
+    /// Unmapped colors, alpha and transparent pixels pass through unchanged.
+    fn remap_colors(
+        &self,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        mapping: HashMap<(u8, u8, u8), (u8, u8, u8)>,
+    ) -> PyResult<Vec<u8>> {
+        validate_rgba(pixels, width, height)?;
+
+        let mut output = pixels.to_vec();
+        for chunk in output.chunks_exact_mut(4).filter(|chunk| chunk[3] > 0) {
+            if let Some(&(r, g, b)) = mapping.get(&(chunk[0], chunk[1], chunk[2])) {
+                chunk[0] = r;
+                chunk[1] = g;
+                chunk[2] = b;
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Where the content leans within the frame, as (x, y) in [-1, 1]
+    ///
+    /// The opaque-pixel centroid normalized so (0, 0) is the frame center and
+    /// (±1, ±1) are the corners; positive y is toward the bottom. Empty sprites
+    /// return (0, 0).
+    fn composition_bias(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<(f64, f64)> {
+        validate_rgba(pixels, width, height)?;
+
+        let Some((cx, cy)) = self.content_centroid(pixels, width) else {
+            return Ok((0.0, 0.0));
+        };
+
+        // Pixel centers sit at +0.5
+        let normalize = |c: f64, length: u32| (c + 0.5) / length as f64 * 2.0 - 1.0;
+        Ok((normalize(cx, width), normalize(cy, height)))
+    }
+
+    /// Split a strip into content segments separated by transparent gutters
+    ///
+    /// `axis` is "horizontal" (scan columns, frames side by side) or "vertical"
+    /// (scan rows). Runs of fully-transparent lines at least `min_gap` long split
+    /// segments; shorter runs stay inside the surrounding frame. Returns
+    /// (start, length) per segment with leading/trailing transparency trimmed.
+    fn split_by_gaps(
+        &self,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        axis: &str,
+        min_gap: u32,
+    ) -> PyResult<Vec<(u32, u32)>> {
+        validate_rgba(pixels, width, height)?;
+
+        let (lines, span) = match axis {
+            "horizontal" => (width, height),
+            "vertical" => (height, width),
+            _ => {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "axis must be 'horizontal' or 'vertical'"
+                ))
+            }
+        };
+        if min_gap == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "min_gap must be greater than 0"
+            ));
+        }
+
+        let line_has_content = |line: u32| {
+            (0..span).any(|offset| {
+                let (x, y) = if axis == "horizontal" { (line, offset) } else { (offset, line) };
+                pixels[((y * width + x) * 4 + 3) as usize] > 0
+            })
+        };
+
+        let mut segments = Vec::new();
+        let mut current: Option<(u32, u32)> = None; // (start, last content line)
+        for line in 0..lines {
+            if !line_has_content(line) {
+                continue;
+            }
+            current = match current {
+                Some((start, last)) if line - last - 1 < min_gap => Some((start, line)),
+                Some((start, last)) => {
+                    segments.push((start, last - start + 1));
+                    Some((line, line))
+                }
+                None => Some((line, line)),
+            };
+        }
+        if let Some((start, last)) = current {
+            segments.push((start, last - start + 1));
+        }
+
+        Ok(segments)
+    }
+
+    /// Per-channel median color of opaque pixels
+    ///
+    /// Robust to outliers that skew the mean in `get_dominant_color`. Uses a
+    /// 256-bin histogram per channel; for even counts the lower median is used.
+    /// Empty sprites return (0, 0, 0).
+    fn median_color(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<(u8, u8, u8)> {
+        validate_rgba(pixels, width, height)?;
+
+        let mut histograms = [[0u32; 256]; 3];
+        let mut count = 0u32;
+        for chunk in pixels.chunks_exact(4).filter(|chunk| chunk[3] > 0) {
+            for c in 0..3 {
+                histograms[c][chunk[c] as usize] += 1;
+            }
+            count += 1;
+        }
+        if count == 0 {
+            return Ok((0, 0, 0));
+        }
+
+        let median = |histogram: &[u32; 256]| {
+            let target = count.div_ceil(2);
+            let mut seen = 0;
+            for (value, &bin) in histogram.iter().enumerate() {
+                seen += bin;
+                if seen >= target {
+                    return value as u8;
+                }
+            }
+            255
+        };
+
+        Ok((median(&histograms[0]), median(&histograms[1]), median(&histograms[2])))
+    }
+
+    /// Compact color descriptor for similarity search
+    ///
+    /// Divides the alpha bounding box into a `grid`×`grid` mesh and returns the
+    /// mean opaque color of each cell in row-major order. Cells with no opaque
+    /// pixels (or every cell of an empty sprite) are (0, 0, 0). Compare two
+    /// signatures with L2 distance. `grid` may not exceed the shorter side of
+    /// the image, which keeps the signature no larger than the pixel count.
+    fn color_signature(&self, pixels: &[u8], width: u32, height: u32, grid: u32) -> PyResult<Vec<(u8, u8, u8)>> {
+        validate_rgba(pixels, width, height)?;
+
+        if grid == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "grid must be greater than 0"
+            ));
+        }
+        if grid > width.min(height) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "grid must not exceed the image's shorter side"
+            ));
+        }
+
+        let cells = grid as usize * grid as usize;
+        let mut sums = vec![[0u64; 3]; cells];
+        let mut counts = vec![0u64; cells];
+
+        let (bx, by, bw, bh) = self.calculate_alpha_bounding_box(pixels, width, height);
+        for y in by..by + bh {
+            let cell_y = ((y - by) as u64 * grid as u64 / bh as u64) as usize;
+            for x in bx..bx + bw {
+                let idx = ((y * width + x) * 4) as usize;
+                if pixels[idx + 3] == 0 {
+                    continue;
+                }
+                let cell_x = ((x - bx) as u64 * grid as u64 / bw as u64) as usize;
+                let cell = cell_y * grid as usize + cell_x;
+                for c in 0..3 {
+                    sums[cell][c] += pixels[idx + c] as u64;
+                }
+                counts[cell] += 1;
+            }
+        }
+
+        Ok(sums
+            .iter()
+            .zip(&counts)
+            .map(|(sum, &count)| {
+                if count == 0 {
+                    return (0, 0, 0);
+                }
+                let mean = |c: usize| ((sum[c] + count / 2) / count) as u8;
+                (mean(0), mean(1), mean(2))
+            })
+            .collect())
+    }
+
+    /// Composite the sprite over a solid background color
+    ///
+    /// Straight-alpha blend per channel; the returned RGBA buffer is fully
+    /// opaque. Used for exporting to formats without an alpha channel.
+    fn flatten(&self, pixels: &[u8], width: u32, height: u32, background: (u8, u8, u8)) -> PyResult<Vec<u8>> {
+        validate_rgba(pixels, width, height)?;
+
+        let background = [background.0, background.1, background.2];
+        let mut output = Vec::with_capacity(pixels.len());
+        for chunk in pixels.chunks_exact(4) {
+            let alpha = chunk[3] as u32;
+            for c in 0..3 {
+                let blended = chunk[c] as u32 * alpha + background[c] as u32 * (255 - alpha);
+                output.push(((blended + 127) / 255) as u8);
+            }
+            output.push(255);
+        }
+
+        Ok(output)
+    }
+
+    /// Crop away a uniform solid-color frame border
+    ///
+    /// The border color is taken from the top-left corner and is only trimmed
+    /// when all four corners match it within `tolerance` (per RGBA channel).
+    /// Rows and columns are peeled inward until one contains differing content.
+    /// Returns (pixels, width, height); a sprite that is entirely border
+    /// yields an empty buffer with zero dimensions.
+    fn trim_border(&self, pixels: &[u8], width: u32, height: u32, tolerance: u8) -> PyResult<(Vec<u8>, u32, u32)> {
+        validate_rgba(pixels, width, height)?;
+
+        if width == 0 || height == 0 {
+            return Ok((pixels.to_vec(), width, height));
+        }
+
+        let pixel = |x: u32, y: u32| {
+            let idx = ((y * width + x) * 4) as usize;
+            &pixels[idx..idx + 4]
+        };
+        let border = pixel(0, 0);
+        let matches = |x: u32, y: u32| {
+            pixel(x, y)
+                .iter()
+                .zip(border)
+                .all(|(&a, &b)| a.abs_diff(b) <= tolerance)
+        };
+
+        let corners = [(width - 1, 0), (0, height - 1), (width - 1, height - 1)];
+        if !corners.iter().all(|&(x, y)| matches(x, y)) {
+            return Ok((pixels.to_vec(), width, height));
+        }
+
+        // Half-open content window [left, right) × [top, bottom)
+        let (mut left, mut top, mut right, mut bottom) = (0, 0, width, height);
+        while top < bottom && (left..right).all(|x| matches(x, top)) {
+            top += 1;
+        }
+        while bottom > top && (left..right).all(|x| matches(x, bottom - 1)) {
+            bottom -= 1;
+        }
+        while left < right && (top..bottom).all(|y| matches(left, y)) {
+            left += 1;
+        }
+        while right > left && (top..bottom).all(|y| matches(right - 1, y)) {
+            right -= 1;
+        }
+
+        if left == right || top == bottom {
+            return Ok((Vec::new(), 0, 0));
+        }
+
+        let (crop_width, crop_height) = (right - left, bottom - top);
+        let cropped = self.crop_padded(pixels, width, height, left as i64, top as i64, crop_width, crop_height);
+        Ok((cropped, crop_width, crop_height))
+    }
+
+    /// Luminance histogram equalization over opaque pixels
+    ///
+    /// Builds the CDF of luma (per `luma_weights`) and remaps each pixel's luma
+    /// through it. Chroma (R−Y, B−Y) is preserved by shifting all three
+    /// channels by the same luma delta, so hue and saturation are kept up to
+    /// channel clipping.
+    /// Alpha and transparent pixels are untouched.
+    fn equalize(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<Vec<u8>> {
+        validate_rgba(pixels, width, height)?;
+
+        let luma_bin = |chunk: &[u8]| self.luminance(chunk[0], chunk[1], chunk[2]).round() as usize;
+
+        let mut histogram = [0u64; 256];
+        for chunk in pixels.chunks_exact(4).filter(|chunk| chunk[3] > 0) {
+            histogram[luma_bin(chunk)] += 1;
+        }
+
+        let total: u64 = histogram.iter().sum();
+        let cdf_min = histogram.iter().copied().find(|&count| count > 0).unwrap_or(0);
+        if total == cdf_min {
+            // Empty or single-luma sprite: nothing to spread
+            return Ok(pixels.to_vec());
+        }
+
+        let mut lut = [0f32; 256];
+        let mut cumulative = 0u64;
+        for (value, &count) in histogram.iter().enumerate() {
+            cumulative += count;
+            lut[value] = cumulative.saturating_sub(cdf_min) as f32 / (total - cdf_min) as f32 * 255.0;
+        }
+
+        let mut output = pixels.to_vec();
+        for chunk in output.chunks_exact_mut(4).filter(|chunk| chunk[3] > 0) {
+            let luma = self.luminance(chunk[0], chunk[1], chunk[2]);
+            let delta = lut[luma_bin(chunk)] - luma;
+            for channel in &mut chunk[..3] {
+                *channel = (*channel as f32 + delta).round().clamp(0.0, 255.0) as u8;
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Anti-aliasing score of the silhouette edge
+    ///
+    /// Boundary pixels are opaque pixels with a transparent 4-neighbour (the
+    /// area outside the image counts as transparent). Returns the fraction of
+    /// them with partial alpha (1–254): near 1.0 is smoothly anti-aliased, near
+    /// 0.0 is hard-edged pixel art. Empty sprites return 0.0.
+    fn edge_smoothness(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<f64> {
+        validate_rgba(pixels, width, height)?;
+
+        let boundary = self.boundary_pixels(pixels, width, height);
+        let partial = boundary.iter().filter(|&&i| pixels[i * 4 + 3] < 255).count();
+
+        if boundary.is_empty() {
+            return Ok(0.0);
+        }
+        Ok(partial as f64 / boundary.len() as f64)
+    }
+
+    /// Copy of the sprite with the alpha bounding box outlined in `color`
+    ///
+    /// Draws a 1-pixel rectangle on the outermost rows/columns of the content
+    /// box, overwriting those pixels. Empty sprites are returned unchanged.
+    fn draw_bounds(&self, pixels: &[u8], width: u32, height: u32, color: (u8, u8, u8, u8)) -> PyResult<Vec<u8>> {
+        validate_rgba(pixels, width, height)?;
+
+        let mut output = pixels.to_vec();
+        let (bx, by, bw, bh) = self.calculate_alpha_bounding_box(pixels, width, height);
+        if bw == 0 || bh == 0 {
+            return Ok(output);
+        }
+
+        let color = [color.0, color.1, color.2, color.3];
+        let mut plot = |x: u32, y: u32| {
+            let idx = ((y * width + x) * 4) as usize;
+            output[idx..idx + 4].copy_from_slice(&color);
+        };
+        for x in bx..bx + bw {
+            plot(x, by);
+            plot(x, by + bh - 1);
+        }
+        for y in by..by + bh {
+            plot(bx, y);
+            plot(bx + bw - 1, y);
+        }
+
+        Ok(output)
+    }
+
+    /// RGBA of the pixel at (x, y)
+    fn pixel_at(&self, pixels: &[u8], width: u32, height: u32, x: u32, y: u32) -> PyResult<(u8, u8, u8, u8)> {
+        validate_rgba(pixels, width, height)?;
+
+        if x >= width || y >= height {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Pixel ({}, {}) is outside the {}x{} image",
+                x, y, width, height
+            )));
+        }
+
+        let idx = ((y * width + x) * 4) as usize;
+        Ok((pixels[idx], pixels[idx + 1], pixels[idx + 2], pixels[idx + 3]))
+    }
+
+    /// Largest `aspect_w`:`aspect_h` crop centered on the content centroid
+    ///
+    /// The crop is as large as the image allows for the requested ratio and is
+    /// shifted to stay within bounds. Empty sprites are centered on the image.
+    /// Returns (pixels, width, height).
+    fn crop_to_aspect(
+        &self,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        aspect_w: u32,
+        aspect_h: u32,
+    ) -> PyResult<(Vec<u8>, u32, u32)> {
+        validate_rgba(pixels, width, height)?;
+
+        if aspect_w == 0 || aspect_h == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "aspect_w and aspect_h must be greater than 0"
+            ));
+        }
+
+        let (aw, ah) = (aspect_w as u64, aspect_h as u64);
+        let (crop_width, crop_height) = if width as u64 * ah <= height as u64 * aw {
+            (width, (width as u64 * ah / aw) as u32)
+        } else {
+            ((height as u64 * aw / ah) as u32, height)
+        };
+
+        let (cx, cy) = self
+            .content_centroid(pixels, width)
+            .unwrap_or(((width as f64 - 1.0) / 2.0, (height as f64 - 1.0) / 2.0));
+        let place = |center: f64, crop_len: u32, image_len: u32| -> i64 {
+            let ideal = (center + 0.5 - crop_len as f64 / 2.0).round() as i64;
+            ideal.clamp(0, (image_len - crop_len) as i64)
+        };
+        let origin_x = place(cx, crop_width, width);
+        let origin_y = place(cy, crop_height, height);
+
+        let cropped = self.crop_padded(pixels, width, height, origin_x, origin_y, crop_width, crop_height);
+        Ok((cropped, crop_width, crop_height))
+    }
+
+    /// Opaque pixel count per column (length `width`)
+    ///
+    /// Valleys in the projection mark gutters between sprites on a sheet.
+    fn column_profile(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<Vec<u32>> {
+        validate_rgba(pixels, width, height)?;
+
+        let mut profile = vec![0u32; width as usize];
+        for (i, chunk) in pixels.chunks_exact(4).enumerate() {
+            if chunk[3] > 0 {
+                profile[i % width as usize] += 1;
+            }
+        }
+
+        Ok(profile)
+    }
+
+    /// Opaque pixel count per row (length `height`)
+    fn row_profile(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<Vec<u32>> {
+        validate_rgba(pixels, width, height)?;
+
+        let mut profile = vec![0u32; height as usize];
+        for (i, chunk) in pixels.chunks_exact(4).enumerate() {
+            if chunk[3] > 0 {
+                profile[i / width as usize] += 1;
+            }
+        }
+
+        Ok(profile)
+    }
+
+    /// Human-readable hue family of the sprite for tagging
+    ///
+    /// Averages opaque-pixel hue as a circular mean weighted by HSV saturation,
+    /// then names the resulting angle: "red", "orange", "yellow", "green",
+    /// "cyan", "blue", "purple" or "magenta". Returns "gray" when the mean
+    /// saturation is below 0.15 (and for empty sprites).
+    fn dominant_hue_name(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<String> {
+        validate_rgba(pixels, width, height)?;
+
+        let mut sum_x = 0.0f64;
+        let mut sum_y = 0.0f64;
+        let mut saturation_sum = 0.0f64;
+        let mut count = 0u64;
+        for chunk in pixels.chunks_exact(4).filter(|chunk| chunk[3] > 0) {
+            let (hue, saturation) = self.hue_saturation(chunk[0], chunk[1], chunk[2]);
+            let radians = hue.to_radians();
+            sum_x += saturation * radians.cos();
+            sum_y += saturation * radians.sin();
+            saturation_sum += saturation;
+            count += 1;
+        }
+
+        if count == 0 || saturation_sum / (count as f64) < 0.15 || (sum_x == 0.0 && sum_y == 0.0) {
+            return Ok("gray".to_string());
+        }
+
+        let hue = sum_y.atan2(sum_x).to_degrees().rem_euclid(360.0);
+        let name = match hue {
+            h if h < 15.0 => "red",
+            h if h < 45.0 => "orange",
+            h if h < 70.0 => "yellow",
+            h if h < 160.0 => "green",
+            h if h < 200.0 => "cyan",
+            h if h < 260.0 => "blue",
+            h if h < 290.0 => "purple",
+            h if h < 345.0 => "magenta",
+            _ => "red",
+        };
+
+        Ok(name.to_string())
+    }
+
+    /// True when the opaque fill ratio is below `min_fill`
+    ///
+    /// Catches near-empty frames with a few stray pixels as well as fully
+    /// transparent ones. Zero-area images are always blank.
+    fn is_blank(&self, pixels: &[u8], width: u32, height: u32, min_fill: f64) -> PyResult<bool> {
+        if !(0.0..=1.0).contains(&min_fill) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "min_fill must be between 0.0 and 1.0"
+            ));
+        }
+
+        let opaque = self.opaque_pixel_count(pixels, width, height)?;
+        let total = width as u64 * height as u64;
+        if total == 0 {
+            return Ok(true);
+        }
+
+        Ok((opaque as f64 / total as f64) < min_fill)
+    }
+
+    /// Mean RGB of the opaque pixels in each `classify_color` category
+    ///
+    /// Only categories that occur are present. Classification honours
+    /// `linearize`, while the reported colors are the original sRGB values.
+    fn category_colors(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<HashMap<String, (u8, u8, u8)>> {
+        validate_rgba(pixels, width, height)?;
+
+        let linear_lut = self.linearize.then(srgb_to_linear_lut);
+        let mut sums: HashMap<String, ([u64; 3], u64)> = HashMap::new();
+        for chunk in pixels.chunks_exact(4).filter(|chunk| chunk[3] > 0) {
+            let (r, g, b) = (chunk[0], chunk[1], chunk[2]);
+            let category = match &linear_lut {
+                Some(lut) => self.classify_color(lut[r as usize], lut[g as usize], lut[b as usize]),
+                None => self.classify_color(r, g, b),
+            };
+            let (sum, count) = sums.entry(category).or_insert(([0; 3], 0));
+            for c in 0..3 {
+                sum[c] += chunk[c] as u64;
+            }
+            *count += 1;
+        }
+
+        Ok(sums
+            .into_iter()
+            .map(|(category, (sum, count))| {
+                let mean = |c: usize| ((sum[c] + count / 2) / count) as u8;
+                (category, (mean(0), mean(1), mean(2)))
+            })
+            .collect())
+    }
+
+    /// Detect interlace-style scanlines (alternate rows systematically darker)
+    ///
+    /// Compares the mean luminance of opaque pixels on even rows against odd
+    /// rows; true when the darker set falls more than `SCANLINE_MIN_CONTRAST`
+    /// (15%) below the brighter one. Needs opaque pixels on both parities.
+    fn detect_scanlines(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<bool> {
+        validate_rgba(pixels, width, height)?;
+
+        let Some((even, odd)) = self.row_parity_luma(pixels, width) else {
+            return Ok(false);
+        };
+        let (dark, bright) = (even.min(odd), even.max(odd));
+
+        Ok(bright > 0.0 && (bright - dark) / bright > SCANLINE_MIN_CONTRAST)
+    }
+
+    /// Brighten the darker row parity to undo scanline darkening
+    ///
+    /// RGB on the darker even/odd row set is scaled by the ratio of the mean
+    /// luminances, which keeps hue and saturation (up to clipping); alpha is
+    /// untouched. Returns an unchanged copy when `detect_scanlines` is false.
+    fn remove_scanlines(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<Vec<u8>> {
+        let mut output = pixels.to_vec();
+        if !self.detect_scanlines(pixels, width, height)? {
+            return Ok(output);
+        }
+        let Some((even, odd)) = self.row_parity_luma(pixels, width) else {
+            return Ok(output);
+        };
+
+        let (dark_parity, gain) = if even < odd { (0, odd / even) } else { (1, even / odd) };
+        if !gain.is_finite() {
+            // Pure black rows have no color left to scale
+            return Ok(output);
+        }
+
+        for (i, chunk) in output.chunks_exact_mut(4).enumerate() {
+            if chunk[3] == 0 || (i / width as usize) % 2 != dark_parity {
+                continue;
+            }
+            for channel in &mut chunk[..3] {
+                *channel = (*channel as f64 * gain).round().min(255.0) as u8;
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Per column, the y of the lowest opaque pixel (-1 for empty columns)
+    ///
+    /// The silhouette's contact edge, e.g. for projecting ground shadows.
+    fn bottom_profile(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<Vec<i32>> {
+        validate_rgba(pixels, width, height)?;
+
+        Ok((0..width)
+            .map(|x| {
+                (0..height)
+                    .rev()
+                    .find(|&y| pixels[((y * width + x) * 4 + 3) as usize] > 0)
+                    .map_or(-1, |y| y as i32)
+            })
+            .collect())
+    }
+
+    /// Per column, the y of the highest opaque pixel (-1 for empty columns)
+    fn top_profile(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<Vec<i32>> {
+        validate_rgba(pixels, width, height)?;
+
+        Ok((0..width)
+            .map(|x| {
+                (0..height)
+                    .find(|&y| pixels[((y * width + x) * 4 + 3) as usize] > 0)
+                    .map_or(-1, |y| y as i32)
+            })
+            .collect())
+    }
+
+    /// Composite the sprite over a blurred black drop shadow
+    ///
+    /// The shadow is the alpha mask shifted by (`offset_x`, `offset_y`), box
+    /// blurred with radius `blur` and scaled to `shadow_alpha`. The canvas
+    /// grows to hold both the sprite and the full blurred shadow. Returns
+    /// (pixels, width, height).
+    #[allow(clippy::too_many_arguments)]
+    fn add_drop_shadow(
+        &self,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        offset_x: i32,
+        offset_y: i32,
+        shadow_alpha: u8,
+        blur: u32,
+    ) -> PyResult<(Vec<u8>, u32, u32)> {
+        validate_rgba(pixels, width, height)?;
+
+        let (w, h) = (width as i64, height as i64);
+        let (ox, oy, radius) = (offset_x as i64, offset_y as i64, blur as i64);
+        let min_x = 0.min(ox - radius);
+        let min_y = 0.min(oy - radius);
+        let max_x = w.max(w + ox + radius);
+        let max_y = h.max(h + oy + radius);
+        let canvas_width = u32::try_from(max_x - min_x).ok();
+        let canvas_height = u32::try_from(max_y - min_y).ok();
+        let (Some(canvas_width), Some(canvas_height)) = (canvas_width, canvas_height) else {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Shadow canvas is too large"
+            ));
+        };
+        rgba_len(canvas_width, canvas_height)?;
+        let (cw, ch) = (canvas_width as usize, canvas_height as usize);
+
+        // Offset alpha mask in canvas coordinates
+        let mut shadow = vec![0.0f32; cw * ch];
+        for (i, chunk) in pixels.chunks_exact(4).enumerate() {
+            if chunk[3] == 0 {
+                continue;
+            }
+            let x = (i as i64 % w + ox - min_x) as usize;
+            let y = (i as i64 / w + oy - min_y) as usize;
+            shadow[y * cw + x] = chunk[3] as f32 / 255.0;
+        }
+        let shadow = self.box_blur(&shadow, cw, ch, blur as usize);
+
+        let strength = shadow_alpha as f32 / 255.0;
+        let mut output = vec![0u8; cw * ch * 4];
+        for (i, out) in output.chunks_exact_mut(4).enumerate() {
+            let shadow_a = shadow[i] * strength;
+
+            // Sprite pixel at this canvas position, if any
+            let sx = (i % cw) as i64 + min_x;
+            let sy = (i / cw) as i64 + min_y;
+            let (rgb, sprite_a) = if sx >= 0 && sx < w && sy >= 0 && sy < h {
+                let idx = ((sy * w + sx) * 4) as usize;
+                ([pixels[idx], pixels[idx + 1], pixels[idx + 2]], pixels[idx + 3] as f32 / 255.0)
+            } else {
+                ([0; 3], 0.0)
+            };
+
+            // Sprite "over" a black shadow
+            let alpha = sprite_a + shadow_a * (1.0 - sprite_a);
+            if alpha <= 0.0 {
+                continue;
+            }
+            for c in 0..3 {
+                out[c] = (rgb[c] as f32 * sprite_a / alpha).round().min(255.0) as u8;
+            }
+            out[3] = (alpha * 255.0).round() as u8;
+        }
+
+        Ok((output, canvas_width, canvas_height))
+    }
+
+    /// Mirror axis (through the content centroid) that best matches the mask
+    ///
+    /// Candidate axes are sampled every 15° over [0°, 180°); 0° is horizontal
+    /// and 90° vertical, measured counter-clockwise on screen. Each opaque
+    /// pixel is reflected across the axis and the score is the fraction that
+    /// lands on an opaque pixel. Ties (common on tiny masks, where reflections
+    /// snap to the grid) go to the axis whose reflections land closest to pixel
+    /// centers. Returns (angle, score); empty sprites return (0.0, 0.0).
+    fn best_symmetry_axis(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<(f64, f64)> {
+        validate_rgba(pixels, width, height)?;
+
+        let Some((cx, cy)) = self.content_centroid(pixels, width) else {
+            return Ok((0.0, 0.0));
+        };
+        let opaque: Vec<(f64, f64)> = pixels
+            .chunks_exact(4)
+            .enumerate()
+            .filter(|(_, chunk)| chunk[3] > 0)
+            .map(|(i, _)| ((i as u32 % width) as f64, (i as u32 / width) as f64))
+            .collect();
+        let is_opaque = |x: f64, y: f64| {
+            let (x, y) = (x.round(), y.round());
+            x >= 0.0 && y >= 0.0 && x < width as f64 && y < height as f64
+                && pixels[((y as u32 * width + x as u32) * 4 + 3) as usize] > 0
+        };
+
+        let mut best = (0.0, -1.0);
+        let mut best_error = f64::INFINITY;
+        for step in 0..12 {
+            let angle = step as f64 * 15.0;
+            // Screen y points down, so counter-clockwise flips the y component
+            let (ux, uy) = (angle.to_radians().cos(), -angle.to_radians().sin());
+            let mut matched = 0usize;
+            let mut error = 0.0;
+            for &(x, y) in &opaque {
+                let (dx, dy) = (x - cx, y - cy);
+                let along = dx * ux + dy * uy;
+                let (rx, ry) = (cx + 2.0 * along * ux - dx, cy + 2.0 * along * uy - dy);
+                if is_opaque(rx, ry) {
+                    matched += 1;
+                    error += (rx - rx.round()).powi(2) + (ry - ry.round()).powi(2);
+                }
+            }
+            let score = matched as f64 / opaque.len() as f64;
+            if score > best.1 + 1e-9 || ((score - best.1).abs() <= 1e-9 && error < best_error - 1e-9) {
+                best = (angle, score);
+                best_error = error;
+            }
+        }
+
+        Ok(best)
+    }
+
+    /// Ellipse fit from the second moments of the opaque pixels
+    ///
+    /// Returns (center_x, center_y, semi_major, semi_minor, angle). The center
+    /// is in continuous image coordinates (pixel centers at +0.5); semi-axes
+    /// are 2·sqrt(eigenvalue) of the covariance, exact for a filled ellipse;
+    /// the angle of the major axis is in degrees, counter-clockwise on screen
+    /// from the +x axis, in (-90, 90]. Empty sprites return all zeros.
+    fn bounding_ellipse(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<(f64, f64, f64, f64, f64)> {
+        validate_rgba(pixels, width, height)?;
+
+        Ok(self.ellipse_fit(pixels, width).unwrap_or((0.0, 0.0, 0.0, 0.0, 0.0)))
+    }
+
+    /// Set the named colors used by `classify_by_palette`
+    fn set_reference_palette(&mut self, palette: Vec<(String, (u8, u8, u8))>) -> PyResult<()> {
+        if palette.is_empty() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Reference palette must not be empty"
+            ));
+        }
+
+        self.reference_palette = palette;
+        Ok(())
+    }
+
+    /// Classify against the reference palette instead of the built-in categories
+    ///
+    /// Each opaque pixel is assigned to the nearest palette entry by squared
+    /// RGB distance (earlier entries win ties). Returns (material_type,
+    /// profile) where the profile is keyed by palette names and the material
+    /// type is the most common name ("unknown" for empty sprites).
+    fn classify_by_palette(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<(String, HashMap<String, f64>)> {
+        validate_rgba(pixels, width, height)?;
+
+        if self.reference_palette.is_empty() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "No reference palette set; call set_reference_palette first"
+            ));
+        }
+
+        let mut counts = vec![0u64; self.reference_palette.len()];
+        let mut total = 0u64;
+        for chunk in pixels.chunks_exact(4).filter(|chunk| chunk[3] > 0) {
+            let distance = |&(_, (r, g, b)): &(String, (u8, u8, u8))| {
+                let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+                d(chunk[0], r) + d(chunk[1], g) + d(chunk[2], b)
+            };
+            let nearest = (0..self.reference_palette.len())
+                .min_by_key(|&i| distance(&self.reference_palette[i]))
+                .unwrap_or(0);
+            counts[nearest] += 1;
+            total += 1;
+        }
+
+        let mut profile: HashMap<String, f64> = HashMap::new();
+        if total == 0 {
+            return Ok(("unknown".to_string(), profile));
+        }
+        for ((name, _), &count) in self.reference_palette.iter().zip(&counts) {
+            if count > 0 {
+                // Duplicate names pool their pixels
+                *profile.entry(name.clone()).or_insert(0.0) += count as f64 / total as f64;
+            }
+        }
+        // Most common name; ties go to the earlier palette entry
+        let mut material_type = String::new();
+        let mut best_ratio = 0.0;
+        for (name, _) in &self.reference_palette {
+            let ratio = profile.get(name).copied().unwrap_or(0.0);
+            if ratio > best_ratio {
+                best_ratio = ratio;
+                material_type = name.clone();
+            }
+        }
+
+        Ok((material_type, profile))
+    }
+
+    /// Copy with alpha hardened to 0 (alpha <= `cutoff`) or 255, RGB untouched
+    ///
+    /// In-place counterpart of `binarize_alpha`; afterwards `alpha_levels`
+    /// reports at most 2 levels.
+    fn threshold_alpha(&self, pixels: &[u8], width: u32, height: u32, cutoff: u8) -> PyResult<Vec<u8>> {
+        validate_rgba(pixels, width, height)?;
+
+        let mut output = pixels.to_vec();
+        for chunk in output.chunks_exact_mut(4) {
+            chunk[3] = if chunk[3] > cutoff { 255 } else { 0 };
+        }
+
+        Ok(output)
+    }
+
+    /// Number of connected opaque components with at least `min_area` pixels
+    ///
+    /// Same labelling as `find_components`, without building the boxes.
+    #[pyo3(signature = (pixels, width, height, min_area = 1, connectivity = 4))]
+    fn count_sprites(
+        &self,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        min_area: u32,
+        connectivity: u8,
+    ) -> PyResult<u32> {
+        validate_rgba(pixels, width, height)?;
+        validate_connectivity(connectivity)?;
+
+        let (_, areas) = self.label_components(pixels, width, height, connectivity);
+        Ok(areas.iter().filter(|&&area| area >= min_area).count() as u32)
+    }
+
+    /// Predict how well the sprite reads when shrunk to `target`×`target`
+    ///
+    /// Box-downsamples to the target size, scales back up to the original size
+    /// and compares luminance (transparent pixels as black) with the original
+    /// using SSIM averaged over 8×8 windows. Near 1.0 means little detail was
+    /// lost; low values flag icons too detailed for the size. Zero-area
+    /// sprites return 1.0.
+    fn small_size_legibility(&self, pixels: &[u8], width: u32, height: u32, target: u32) -> PyResult<f64> {
+        validate_rgba(pixels, width, height)?;
+
+        if target == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "target must be greater than 0"
+            ));
+        }
+        rgba_len(target, target)?;
+        if width == 0 || height == 0 {
+            return Ok(1.0);
+        }
+
+        let small = self.box_resample(pixels, width, height, target, target);
+        let round_trip = self.box_resample(&small, target, target, width, height);
+        let original = self.grayscale(pixels);
+        let restored = self.grayscale(&round_trip);
+
+        const WINDOW: u32 = 8;
+        let c1 = (0.01f64 * 255.0).powi(2);
+        let c2 = (0.03f64 * 255.0).powi(2);
+        let mut total = 0.0;
+        let mut windows = 0u32;
+        for wy in (0..height).step_by(WINDOW as usize) {
+            for wx in (0..width).step_by(WINDOW as usize) {
+                let indices: Vec<usize> = (wy..(wy + WINDOW).min(height))
+                    .flat_map(|y| (wx..(wx + WINDOW).min(width)).map(move |x| (y * width + x) as usize))
+                    .collect();
+                let n = indices.len() as f64;
+                let mean = |values: &[f32]| indices.iter().map(|&i| values[i] as f64).sum::<f64>() / n;
+                let (mean_a, mean_b) = (mean(&original), mean(&restored));
+
+                let mut var_a = 0.0;
+                let mut var_b = 0.0;
+                let mut covariance = 0.0;
+                for &i in &indices {
+                    let da = original[i] as f64 - mean_a;
+                    let db = restored[i] as f64 - mean_b;
+                    var_a += da * da;
+                    var_b += db * db;
+                    covariance += da * db;
+                }
+                let (var_a, var_b, covariance) = (var_a / n, var_b / n, covariance / n);
+
+                total += ((2.0 * mean_a * mean_b + c1) * (2.0 * covariance + c2))
+                    / ((mean_a * mean_a + mean_b * mean_b + c1) * (var_a + var_b + c2));
+                windows += 1;
+            }
+        }
+
+        Ok(total / windows as f64)
+    }
+
+    /// Keep only the largest connected opaque component
+    ///
+    /// Every other component becomes fully transparent. Ties go to the
+    /// component whose first pixel comes first in row-major order;
+    /// `connectivity` follows the same 4/8 rules as `find_components`.
+    #[pyo3(signature = (pixels, width, height, connectivity = 4))]
+    fn keep_largest_component(
+        &self,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        connectivity: u8,
+    ) -> PyResult<Vec<u8>> {
+        validate_rgba(pixels, width, height)?;
+        validate_connectivity(connectivity)?;
+
+        let (labels, areas) = self.label_components(pixels, width, height, connectivity);
+
+        // Labels are numbered in row-major order of first pixel, from 1
+        let mut largest = 0;
+        let mut largest_area = 0;
+        for (index, &area) in areas.iter().enumerate() {
+            if area > largest_area {
+                largest = index as u32 + 1;
+                largest_area = area;
+            }
+        }
+
+        let mut output = pixels.to_vec();
+        for (i, &label) in labels.iter().enumerate() {
+            if label != 0 && label != largest {
+                output[i * 4..i * 4 + 4].fill(0);
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Blur the sprite with a separable "box" or "gaussian" kernel
+    ///
+    /// Channels are blurred alpha-premultiplied, so transparent surroundings
+    /// don't darken edges, and the area outside the image counts as
+    /// transparent. The Gaussian uses sigma = radius / 2 truncated at
+    /// `radius`. A radius of 0 returns the input unchanged; radii beyond the
+    /// image's longer side are rejected.
+    fn blur(&self, pixels: &[u8], width: u32, height: u32, radius: u32, mode: &str) -> PyResult<Vec<u8>> {
+        validate_rgba(pixels, width, height)?;
+
+        if mode != "box" && mode != "gaussian" {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "mode must be 'box' or 'gaussian'"
+            ));
+        }
+        if radius > width.max(height) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "radius must not exceed the image's longer side"
+            ));
+        }
+        if radius == 0 {
+            return Ok(pixels.to_vec());
+        }
+
+        let (w, h, radius) = (width as usize, height as usize, radius as usize);
+        let blur_channel = |values: &[f32]| {
+            if mode == "box" {
+                self.box_blur(values, w, h, radius)
+            } else {
+                self.gaussian_blur(values, w, h, radius)
+            }
+        };
+
+        let alpha: Vec<f32> = pixels.chunks_exact(4).map(|chunk| chunk[3] as f32 / 255.0).collect();
+        let blurred_alpha = blur_channel(&alpha);
+        let mut output = vec![0u8; pixels.len()];
+        for c in 0..3 {
+            let premultiplied: Vec<f32> = pixels
+                .chunks_exact(4)
+                .map(|chunk| chunk[c] as f32 * chunk[3] as f32 / 255.0)
+                .collect();
+            let blurred = blur_channel(&premultiplied);
+            for (i, &value) in blurred.iter().enumerate() {
+                if blurred_alpha[i] > 0.0 {
+                    output[i * 4 + c] = (value / blurred_alpha[i]).round().clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+        for (i, &a) in blurred_alpha.iter().enumerate() {
+            output[i * 4 + 3] = (a * 255.0).round().clamp(0.0, 255.0) as u8;
+        }
+
+        Ok(output)
+    }
+
+    /// Predominant outline color of the silhouette
+    ///
+    /// Boundary pixels (as in `edge_smoothness`) are binned by color quantized
+    /// to 16 levels per channel; the mean color of the most populated bin is
+    /// returned, with ties going to the darker bin. Returns (0, 0, 0) when
+    /// there are no boundary pixels.
+    fn outline_color(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<(u8, u8, u8)> {
+        validate_rgba(pixels, width, height)?;
+
+        let mut bins: HashMap<(u8, u8, u8), ([u64; 3], u64)> = HashMap::new();
+        for i in self.boundary_pixels(pixels, width, height) {
+            let rgb = &pixels[i * 4..i * 4 + 3];
+            let (sum, count) = bins.entry((rgb[0] >> 4, rgb[1] >> 4, rgb[2] >> 4)).or_insert(([0; 3], 0));
+            for c in 0..3 {
+                sum[c] += rgb[c] as u64;
+            }
+            *count += 1;
+        }
+
+        let modal = bins
+            .into_iter()
+            .max_by(|(key_a, (_, count_a)), (key_b, (_, count_b))| {
+                // On equal counts the smaller (darker) key compares greater
+                count_a.cmp(count_b).then(key_b.cmp(key_a))
+            });
+        let Some((_, (sum, count))) = modal else {
+            return Ok((0, 0, 0));
+        };
+
+        let mean = |c: usize| ((sum[c] + count / 2) / count) as u8;
+        Ok((mean(0), mean(1), mean(2)))
+    }
+
+    /// Fractions of pixels that are (transparent, semi-transparent, opaque)
+    ///
+    /// Bands are alpha <= `low`, `low` < alpha < `high`, and alpha >= `high`;
+    /// when `low == high` the transparent band claims that value. Zero-area
+    /// images return (0.0, 0.0, 0.0).
+    fn alpha_bands(&self, pixels: &[u8], width: u32, height: u32, low: u8, high: u8) -> PyResult<(f64, f64, f64)> {
+        validate_rgba(pixels, width, height)?;
+
+        if low > high {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "low must be less than or equal to high"
+            ));
+        }
+
+        let mut counts = [0u64; 3];
+        for chunk in pixels.chunks_exact(4) {
+            let band = if chunk[3] <= low {
+                0
+            } else if chunk[3] < high {
+                1
+            } else {
+                2
+            };
+            counts[band] += 1;
+        }
+
+        let total = (pixels.len() / 4) as f64;
+        if total == 0.0 {
+            return Ok((0.0, 0.0, 0.0));
+        }
+        Ok((counts[0] as f64 / total, counts[1] as f64 / total, counts[2] as f64 / total))
+    }
+
+    /// Full mip chain from the original down to 1×1
+    ///
+    /// Each level halves the previous one (rounding down, never below 1) with
+    /// the alpha-weighted box filter, so transparent texels don't bleed color.
+    /// The first entry is the original. Returns (pixels, width, height) per level.
+    #[allow(clippy::type_complexity)]
+    fn generate_mipmaps(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<Vec<(Vec<u8>, u32, u32)>> {
+        validate_rgba(pixels, width, height)?;
+
+        let mut levels = vec![(pixels.to_vec(), width, height)];
+        if width == 0 || height == 0 {
+            return Ok(levels);
+        }
+
+        let (mut level_width, mut level_height) = (width, height);
+        while level_width > 1 || level_height > 1 {
+            let next_width = (level_width / 2).max(1);
+            let next_height = (level_height / 2).max(1);
+            let previous = &levels[levels.len() - 1].0;
+            let next = self.box_resample(previous, level_width, level_height, next_width, next_height);
+            levels.push((next, next_width, next_height));
+            (level_width, level_height) = (next_width, next_height);
+        }
+
+        Ok(levels)
+    }
+
+    /// Whether the sprite wraps without visible seams when tiled
+    ///
+    /// Compares the left column with the right and the top row with the
+    /// bottom; true when the mean per-pixel RGBA difference over both pairs is
+    /// within `tolerance`. Zero-area images are never seamless.
+    fn is_seamless(&self, pixels: &[u8], width: u32, height: u32, tolerance: u8) -> PyResult<bool> {
+        validate_rgba(pixels, width, height)?;
+
+        Ok(self
+            .seam_difference(pixels, width, height)
+            .is_some_and(|difference| difference <= tolerance as f64))
+    }
+
+    /// Alpha bounding box as fractions of the frame: (x, y, width, height)
+    ///
+    /// x and width are divided by the image width, y and height by the image
+    /// height, so all values lie in 0.0-1.0 with the origin at the top-left.
+    /// Empty sprites return (0, 0, 0, 0).
+    fn content_box_normalized(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<(f64, f64, f64, f64)> {
+        validate_rgba(pixels, width, height)?;
+
+        let (x, y, w, h) = self.calculate_alpha_bounding_box(pixels, width, height);
+        if w == 0 || h == 0 {
+            return Ok((0.0, 0.0, 0.0, 0.0));
+        }
+
+        let (fw, fh) = (width as f64, height as f64);
+        Ok((x as f64 / fw, y as f64 / fh, w as f64 / fw, h as f64 / fh))
+    }
+
+    /// Centroid of opaque pixels weighted by luminance ("visual center of mass")
+    ///
+    /// Uses the same pixel-index coordinates as the geometric centroid, so
+    /// dark outlines pull less than bright body pixels. Empty or all-black
+    /// sprites return (0, 0).
+    fn luminance_centroid(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<(f64, f64)> {
+        validate_rgba(pixels, width, height)?;
+
+        let mut sum_x = 0.0;
+        let mut sum_y = 0.0;
+        let mut total_weight = 0.0;
+        for (i, chunk) in pixels.chunks_exact(4).enumerate() {
+            if chunk[3] == 0 {
+                continue;
+            }
+            let weight = self.luminance(chunk[0], chunk[1], chunk[2]) as f64;
+            sum_x += (i as u32 % width) as f64 * weight;
+            sum_y += (i as u32 / width) as f64 * weight;
+            total_weight += weight;
+        }
+
+        if total_weight <= 0.0 {
+            return Ok((0.0, 0.0));
+        }
+        Ok((sum_x / total_weight, sum_y / total_weight))
+    }
+
+    /// Route a sprite as tileable background ("ocean") rather than an item ("vase")
+    ///
+    /// True only when all of these hold:
+    /// - transparency ratio <= 5% (the texture fills its frame),
+    /// - opposite edges match: mean seam difference <= 24 (see `is_seamless`),
+    /// - Sobel edge density <= `edge_threshold` (no strong silhouette/detail),
+    /// - low compactness: content covers >= 90% of the frame border instead of
+    ///   sitting as a self-contained shape inside transparent margins.
+    fn is_tileable_background(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<bool> {
+        validate_rgba(pixels, width, height)?;
+
+        let total = width as u64 * height as u64;
+        let Some(seam_difference) = self.seam_difference(pixels, width, height) else {
+            return Ok(false);
+        };
+        let opaque = pixels.chunks_exact(4).filter(|chunk| chunk[3] > 0).count() as u64;
+        let transparency_ratio = (total - opaque) as f64 / total as f64;
+
+        // Frame border pixels, each counted once
+        let border: Vec<u32> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|&(x, y)| x == 0 || y == 0 || x == width - 1 || y == height - 1)
+            .map(|(x, y)| y * width + x)
+            .collect();
+        let border_opaque = border.iter().filter(|&&i| pixels[(i * 4 + 3) as usize] > 0).count();
+        let border_coverage = border_opaque as f64 / border.len() as f64;
+
+        Ok(transparency_ratio <= TILE_MAX_TRANSPARENCY
+            && seam_difference <= TILE_MAX_SEAM_DIFFERENCE
+            && self.sobel_edge_density(pixels, width, height) <= self.edge_threshold
+            && border_coverage >= TILE_MIN_BORDER_COVERAGE)
+    }
+
+    /// JPEG-style 8×8 blocking score
+    ///
+    /// Ratio of the mean luminance step across 8-pixel grid boundaries to the
+    /// mean step between neighbours inside blocks, over horizontally and
+    /// vertically adjacent opaque pairs. Both means get +1 so flat images
+    /// score 1.0; values well above 1 indicate blocking. Sprites too small to
+    /// contain a block boundary return 1.0.
+    fn blockiness(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<f64> {
+        validate_rgba(pixels, width, height)?;
+
+        let gray = self.grayscale(pixels);
+        let opaque = |i: usize| pixels[i * 4 + 3] > 0;
+
+        // (sum, count) for boundary and within-block steps
+        let mut boundary = (0.0f64, 0u64);
+        let mut within = (0.0f64, 0u64);
+        let mut record = |a: usize, b: usize, on_boundary: bool| {
+            if !opaque(a) || !opaque(b) {
+                return;
+            }
+            let step = (gray[a] - gray[b]).abs() as f64;
+            let bucket = if on_boundary { &mut boundary } else { &mut within };
+            bucket.0 += step;
+            bucket.1 += 1;
+        };
+        for y in 0..height {
+            for x in 0..width {
+                let i = (y * width + x) as usize;
+                if x + 1 < width {
+                    record(i, i + 1, (x + 1) % 8 == 0);
+                }
+                if y + 1 < height {
+                    record(i, i + width as usize, (y + 1) % 8 == 0);
+                }
+            }
+        }
+
+        if boundary.1 == 0 || within.1 == 0 {
+            return Ok(1.0);
+        }
+        let boundary_mean = boundary.0 / boundary.1 as f64;
+        let within_mean = within.0 / within.1 as f64;
+        Ok((boundary_mean + 1.0) / (within_mean + 1.0))
+    }
+
+    /// Most frequent exact RGBA value
+    ///
+    /// Transparent pixels are skipped unless `include_transparent` is set.
+    /// Ties go to the smallest packed RGBA value; returns (0, 0, 0, 0) when no
+    /// pixels are counted.
+    #[pyo3(signature = (pixels, width, height, include_transparent = false))]
+    fn modal_color(
+        &self,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        include_transparent: bool,
+    ) -> PyResult<(u8, u8, u8, u8)> {
+        validate_rgba(pixels, width, height)?;
+
+        let mut counts: HashMap<u32, u32> = HashMap::new();
+        for chunk in pixels.chunks_exact(4) {
+            if chunk[3] > 0 || include_transparent {
+                *counts.entry(u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]])).or_insert(0) += 1;
+            }
+        }
+
+        let modal = counts
+            .into_iter()
+            .max_by(|(key_a, count_a), (key_b, count_b)| count_a.cmp(count_b).then(key_b.cmp(key_a)))
+            .map_or(0, |(key, _)| key);
+
+        let [r, g, b, a] = modal.to_be_bytes();
+        Ok((r, g, b, a))
+    }
+
+    /// Fraction of the alpha bounding box taken up by interior holes
+    ///
+    /// Holes are enclosed transparent regions, as in `count_holes`. High values
+    /// flag hollow frames that waste atlas space. Empty sprites return 0.0.
+    fn hollow_ratio(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<f64> {
+        validate_rgba(pixels, width, height)?;
+
+        let (_, _, bw, bh) = self.calculate_alpha_bounding_box(pixels, width, height);
+        let box_area = bw as u64 * bh as u64;
+        if box_area == 0 {
+            return Ok(0.0);
+        }
+
+        // Holes are enclosed by content, so they always lie inside the box
+        let hole_pixels = self
+            .interior_hole_mask(pixels, width, height)
+            .iter()
+            .filter(|&&hole| hole)
+            .count();
+
+        Ok(hole_pixels as f64 / box_area as f64)
+    }
+
+    /// Apply a caller-supplied 3x3 kernel to the RGB channels
+    ///
+    /// `kernel` is row-major (index 0 is the top-left tap). Each channel
+    /// becomes `sum / divisor + bias`, rounded and clamped to 0-255; edge
+    /// pixels clamp to the border as in `generate_normal_map`. Alpha passes
+    /// through and transparent pixels' RGB is sampled like any other.
+    #[allow(clippy::too_many_arguments)]
+    fn convolve3x3(
+        &self,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        kernel: [f32; 9],
+        divisor: f32,
+        bias: f32,
+    ) -> PyResult<Vec<u8>> {
+        validate_rgba(pixels, width, height)?;
+
+        if divisor == 0.0 || !divisor.is_finite() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "divisor must be a non-zero finite number"
+            ));
+        }
+
+        let w = width as usize;
+        let h = height as usize;
+        let at = |x: isize, y: isize, c: usize| -> f32 {
+            let cx = x.clamp(0, w as isize - 1) as usize;
+            let cy = y.clamp(0, h as isize - 1) as usize;
+            pixels[(cy * w + cx) * 4 + c] as f32
+        };
+
+        let mut output = pixels.to_vec();
+        for y in 0..h {
+            for x in 0..w {
+                let idx = (y * w + x) * 4;
+                for c in 0..3 {
+                    let mut sum = 0.0;
+                    for (k, &weight) in kernel.iter().enumerate() {
+                        let dx = (k % 3) as isize - 1;
+                        let dy = (k / 3) as isize - 1;
+                        sum += weight * at(x as isize + dx, y as isize + dy, c);
+                    }
+                    output[idx + c] = (sum / divisor + bias).round().clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Copy with every alpha multiplied by `factor` (clamped to 0-255), RGB untouched
+    fn scale_alpha(&self, pixels: &[u8], width: u32, height: u32, factor: f32) -> PyResult<Vec<u8>> {
+        validate_rgba(pixels, width, height)?;
+
+        if !(factor >= 0.0 && factor.is_finite()) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "factor must be a finite number >= 0"
+            ));
+        }
+
+        let mut output = pixels.to_vec();
+        for chunk in output.chunks_exact_mut(4) {
+            chunk[3] = (chunk[3] as f32 * factor).round().min(255.0) as u8;
+        }
+
+        Ok(output)
+    }
+
+    /// Number of color-coherent regions (a coarse structure/complexity metric)
+    ///
+    /// Region growing over 4-connected opaque pixels: each region starts at
+    /// the first unvisited pixel in row-major order and absorbs neighbours
+    /// whose RGB is within `merge_tolerance` of that seed (per channel),
+    /// so gradients don't chain into one region. Regions smaller than
+    /// `min_area` pixels (anti-aliasing fringes, stray pixels) are not counted.
+    #[pyo3(signature = (pixels, width, height, merge_tolerance, min_area = 4))]
+    fn segment_count(
+        &self,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        merge_tolerance: u8,
+        min_area: u32,
+    ) -> PyResult<u32> {
+        validate_rgba(pixels, width, height)?;
+
+        let w = width as usize;
+        let h = height as usize;
+        let mut visited = vec![false; w * h];
+        let mut regions = 0u32;
+
+        for start in 0..w * h {
+            if visited[start] || pixels[start * 4 + 3] == 0 {
+                continue;
+            }
+            let seed = &pixels[start * 4..start * 4 + 3];
+            let similar = |i: usize| {
+                pixels[i * 4 + 3] > 0
+                    && pixels[i * 4..i * 4 + 3]
+                        .iter()
+                        .zip(seed)
+                        .all(|(&p, &s)| p.abs_diff(s) <= merge_tolerance)
+            };
+
+            visited[start] = true;
+            let mut stack = vec![start];
+            let mut area = 0u32;
+            while let Some(i) = stack.pop() {
+                area += 1;
+                let (x, y) = (i % w, i / w);
+                let neighbours = [
+                    (x > 0).then(|| i - 1),
+                    (x + 1 < w).then(|| i + 1),
+                    (y > 0).then(|| i - w),
+                    (y + 1 < h).then(|| i + w),
+                ];
+                for n in neighbours.into_iter().flatten() {
+                    if !visited[n] && similar(n) {
+                        visited[n] = true;
+                        stack.push(n);
+                    }
+                }
+            }
+
+            if area >= min_area {
+                regions += 1;
+            }
+        }
+
+        Ok(regions)
+    }
+
+    /// Rotate the sprite so its principal (long) axis is vertical
+    ///
+    /// The axis comes from the same second-moment fit as `bounding_ellipse`;
+    /// the rotation uses bilinear sampling and grows the canvas like `rotate`.
+    /// Returns (pixels, width, height, angle) where angle is the applied
+    /// counter-clockwise rotation in degrees, in (-90, 90]. Empty sprites are
+    /// returned unchanged with angle 0.
+    #[allow(clippy::type_complexity)]
+    fn auto_upright(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<(Vec<u8>, u32, u32, f64)> {
+        validate_rgba(pixels, width, height)?;
+
+        let Some((_, _, _, _, axis_angle)) = self.ellipse_fit(pixels, width) else {
+            return Ok((pixels.to_vec(), width, height, 0.0));
+        };
+
+        // Turn the axis to 90°, taking the shorter way round
+        let mut applied = 90.0 - axis_angle;
+        if applied > 90.0 {
+            applied -= 180.0;
+        }
+
+        let (rotated, rotated_width, rotated_height) = self.rotate_bilinear(pixels, width, height, applied)?;
+        Ok((rotated, rotated_width, rotated_height, applied))
+    }
+
+    /// Opaque pixels that drifted off an approved palette
+    ///
+    /// A pixel is out of gamut when, for every palette color, some RGB channel
+    /// differs by more than `tolerance`. Returns the out-of-gamut fraction of
+    /// opaque pixels (0.0 when there are none) and a one-byte-per-pixel mask
+    /// with 255 marking those pixels.
+    fn out_of_gamut(
+        &self,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        palette: Vec<(u8, u8, u8)>,
+        tolerance: u8,
+    ) -> PyResult<(f64, Vec<u8>)> {
+        validate_rgba(pixels, width, height)?;
+
+        if palette.is_empty() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Palette must not be empty"
+            ));
+        }
+
+        let mut mask = vec![0u8; pixels.len() / 4];
+        let mut opaque = 0u64;
+        let mut outside = 0u64;
+        for (i, chunk) in pixels.chunks_exact(4).enumerate() {
+            if chunk[3] == 0 {
+                continue;
+            }
+            opaque += 1;
+            let in_gamut = palette.iter().any(|&(r, g, b)| {
+                chunk[0].abs_diff(r) <= tolerance
+                    && chunk[1].abs_diff(g) <= tolerance
+                    && chunk[2].abs_diff(b) <= tolerance
+            });
+            if !in_gamut {
+                mask[i] = 255;
+                outside += 1;
+            }
+        }
+
+        let fraction = if opaque > 0 { outside as f64 / opaque as f64 } else { 0.0 };
+        Ok((fraction, mask))
+    }
+
+    /// False-color map of the `classify_color` category of each opaque pixel
+    ///
+    /// wood → brown, stone → gray, grass → green, water → blue, other →
+    /// magenta. Alpha is kept, so transparent pixels stay transparent.
+    /// Classification honours `linearize`, as in `analyze_sprite`.
+    fn classification_overlay(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<Vec<u8>> {
+        validate_rgba(pixels, width, height)?;
+
+        let linear_lut = self.linearize.then(srgb_to_linear_lut);
+        let mut output = vec![0u8; pixels.len()];
+        for (src, dst) in pixels.chunks_exact(4).zip(output.chunks_exact_mut(4)) {
+            if src[3] == 0 {
+                continue;
+            }
+            let (r, g, b) = (src[0], src[1], src[2]);
+            let category = match &linear_lut {
+                Some(lut) => self.classify_color(lut[r as usize], lut[g as usize], lut[b as usize]),
+                None => self.classify_color(r, g, b),
+            };
+            let color: [u8; 3] = match category.as_str() {
+                "wood" => [139, 90, 43],
+                "stone" => [128, 128, 128],
+                "grass" => [40, 180, 40],
+                "water" => [40, 90, 220],
+                _ => [255, 0, 255],
+            };
+            dst[..3].copy_from_slice(&color);
+            dst[3] = src[3];
+        }
+
+        Ok(output)
+    }
+
+    /// Content extent per axis: (content_width / width, content_height / height)
+    ///
+    /// Measured on the alpha bounding box, so a tall thin item reads as a low
+    /// first value and a high second one. Empty sprites return (0, 0).
+    fn extent_ratios(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<(f64, f64)> {
+        validate_rgba(pixels, width, height)?;
+
+        let (_, _, w, h) = self.calculate_alpha_bounding_box(pixels, width, height);
+        if w == 0 || h == 0 {
+            return Ok((0.0, 0.0));
+        }
+
+        Ok((w as f64 / width as f64, h as f64 / height as f64))
+    }
+
+    /// Remove matte halos by bleeding interior color into the soft edge
+    ///
+    /// Every semi-transparent pixel (0 < alpha < 255) takes the RGB of the
+    /// nearest fully opaque pixel, found by a breadth-first search through
+    /// 8-connected non-transparent pixels; alpha is kept. Semi-transparent
+    /// pixels with no opaque pixel reachable are left unchanged.
+    fn defringe(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<Vec<u8>> {
+        validate_rgba(pixels, width, height)?;
+
+        let (w, h) = (width as usize, height as usize);
+        let mut output = pixels.to_vec();
+        let mut visited = vec![false; w * h];
+        let mut queue = VecDeque::new();
+        for (i, chunk) in pixels.chunks_exact(4).enumerate() {
+            if chunk[3] == 255 {
+                visited[i] = true;
+                queue.push_back(i);
+            }
+        }
+
+        while let Some(i) = queue.pop_front() {
+            let (x, y) = (i % w, i / w);
+            for ny in y.saturating_sub(1)..(y + 2).min(h) {
+                for nx in x.saturating_sub(1)..(x + 2).min(w) {
+                    let n = ny * w + nx;
+                    if visited[n] || pixels[n * 4 + 3] == 0 {
+                        continue;
+                    }
+                    visited[n] = true;
+                    output.copy_within(i * 4..i * 4 + 3, n * 4);
+                    queue.push_back(n);
+                }
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Route an asset as "tile", "scene", "icon" or "sprite"
+    ///
+    /// Checked in this order:
+    /// - "tile": `is_tileable_background` holds (fills its frame, seamless,
+    ///   Sobel edge density <= `edge_threshold`),
+    /// - "scene": the longer side is >= 256 px and >= 95% of pixels are opaque,
+    /// - "icon": both sides are <= 32 px and the aspect ratio (long / short
+    ///   side) is <= 1.5,
+    /// - "sprite": everything else.
+    fn asset_class(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<String> {
+        validate_rgba(pixels, width, height)?;
+
+        if self.is_tileable_background(pixels, width, height)? {
+            return Ok("tile".to_string());
+        }
+
+        let total = width as u64 * height as u64;
+        let opaque = pixels.chunks_exact(4).filter(|chunk| chunk[3] > 0).count() as u64;
+        let fill_ratio = if total > 0 { opaque as f64 / total as f64 } else { 0.0 };
+        let (long_side, short_side) = (width.max(height), width.min(height));
+
+        let class = if long_side >= SCENE_MIN_DIM && fill_ratio >= SCENE_MIN_FILL {
+            "scene"
+        } else if long_side <= ICON_MAX_DIM
+            && short_side > 0
+            && long_side as f64 / short_side as f64 <= ICON_MAX_ASPECT
+        {
+            "icon"
+        } else {
+            "sprite"
+        };
+
+        Ok(class.to_string())
+    }
+
+    /// Exact number of distinct colors among opaque pixels
+    ///
+    /// RGB colors are tallied in a fixed 2^24-bit set (2 MiB) with no
+    /// hashing. With `include_alpha`, pixels differing only in alpha count
+    /// separately; the RGBA space is too large for a bitset, so those values
+    /// are sorted and deduplicated instead.
+    fn unique_color_count(&self, pixels: &[u8], width: u32, height: u32, include_alpha: bool) -> PyResult<u32> {
+        validate_rgba(pixels, width, height)?;
+
+        let opaque = pixels.chunks_exact(4).filter(|chunk| chunk[3] > 0);
+        if include_alpha {
+            let mut colors: Vec<u32> = opaque
+                .map(|chunk| u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                .collect();
+            colors.sort_unstable();
+            colors.dedup();
+            return Ok(colors.len() as u32);
+        }
+
+        let mut seen = vec![0u64; (1 << 24) / 64];
+        let mut count = 0u32;
+        for chunk in opaque {
+            let rgb = u32::from_be_bytes([0, chunk[0], chunk[1], chunk[2]]) as usize;
+            let (word, bit) = (rgb / 64, 1u64 << (rgb % 64));
+            if seen[word] & bit == 0 {
+                seen[word] |= bit;
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Dominant light direction as a unit vector (x right, y down)
+    ///
+    /// Sums the Sobel luminance gradient, which points from dark toward
+    /// light, over opaque pixels whose whole 3x3 neighbourhood is opaque, so
+    /// the silhouette against transparency doesn't count as shading. Returns
+    /// (0, 0) when there is no net gradient.
+    fn estimate_light_direction(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<(f64, f64)> {
+        validate_rgba(pixels, width, height)?;
+
+        if width < 3 || height < 3 {
+            return Ok((0.0, 0.0));
+        }
+
+        let gray = self.grayscale(pixels);
+        let at = |x: u32, y: u32| gray[(y * width + x) as usize] as f64;
+        let opaque = |x: u32, y: u32| pixels[((y * width + x) * 4 + 3) as usize] > 0;
+        let mut sum_x = 0.0;
+        let mut sum_y = 0.0;
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                if !(y - 1..=y + 1).all(|ny| (x - 1..=x + 1).all(|nx| opaque(nx, ny))) {
+                    continue;
+                }
+                sum_x += at(x + 1, y - 1) + 2.0 * at(x + 1, y) + at(x + 1, y + 1)
+                    - at(x - 1, y - 1) - 2.0 * at(x - 1, y) - at(x - 1, y + 1);
+                sum_y += at(x - 1, y + 1) + 2.0 * at(x, y + 1) + at(x + 1, y + 1)
+                    - at(x - 1, y - 1) - 2.0 * at(x, y - 1) - at(x + 1, y - 1);
+            }
+        }
+
+        let length = sum_x.hypot(sum_y);
+        if length < 1e-9 {
+            return Ok((0.0, 0.0));
+        }
+        Ok((sum_x / length, sum_y / length))
+    }
+
+    /// Re-frame the sprite to exactly `target_w` × `target_h` without scaling
+    ///
+    /// Centering uses the content (alpha bounding) box, not the frame: the
+    /// box is centered in the target, padding with transparent pixels where
+    /// it is smaller and cropping evenly where it is larger (an odd leftover
+    /// pixel goes to the right/bottom). Empty sprites yield a transparent frame.
+    fn fit_to_size(
+        &self,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        target_w: u32,
+        target_h: u32,
+    ) -> PyResult<Vec<u8>> {
+        validate_rgba(pixels, width, height)?;
+
+        if target_w == 0 || target_h == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "target_w and target_h must be greater than 0"
+            ));
+        }
+        let target_len = rgba_len(target_w, target_h)?;
+
+        let (x, y, w, h) = self.calculate_alpha_bounding_box(pixels, width, height);
+        if w == 0 || h == 0 {
+            return Ok(vec![0u8; target_len]);
+        }
+
+        let origin_x = x as i64 + (w as i64 - target_w as i64) / 2;
+        let origin_y = y as i64 + (h as i64 - target_h as i64) / 2;
+        Ok(self.crop_padded(pixels, width, height, origin_x, origin_y, target_w, target_h))
+    }
+}
+
+impl MaterialTriageEngine {
+    /// Analysis through the optional result cache
+    fn analyze_cached(&self, pixels: &[u8], width: u32, height: u32) -> MaterialDNA {
+        // Only hash the buffer when caching is enabled
+        let mut cache_key = None;
+        if let Some(cache) = self.cache.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+            let key = (fnv1a_hash(pixels), width, height);
+            let check = sip_hash(pixels);
+            if let Some(dna) = cache.get(&key, check) {
+                return dna;
+            }
+            cache_key = Some((key, check));
+        }
+
+        let dna = self.analyze_capped(pixels, width, height);
+
+        if let Some((key, check)) = cache_key {
+            if let Some(cache) = self.cache.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+                cache.insert(key, check, dna.clone());
+            }
         }
+
+        dna
     }
 
-    /// Complete Material Triage Analysis
-    fn analyze_sprite(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<MaterialDNA> {
-        if pixels.len() != (width * height * 4) as usize {
-            return Err(pyo3::exceptions::PyValueError::new_err(
-                "Pixel data length doesn't match dimensions"
-            ));
+    /// Analysis honouring `analysis_max_dim` downsampling
+    fn analyze_capped(&self, pixels: &[u8], width: u32, height: u32) -> MaterialDNA {
+        if let Some(max_dim) = self.analysis_max_dim {
+            if width.max(height) > max_dim {
+                let scale = max_dim as f64 / width.max(height) as f64;
+                let small_width = ((width as f64 * scale).round() as u32).max(1);
+                let small_height = ((height as f64 * scale).round() as u32).max(1);
+                let small = self.box_resample(pixels, width, height, small_width, small_height);
+
+                let mut dna = self.analyze_internal(&small, small_width, small_height);
+                // Box averaging can round faint alpha to 0, so bound the original
+                dna.alpha_bounding_box = self.calculate_alpha_bounding_box(pixels, width, height);
+                dna.is_empty = dna.alpha_bounding_box.2 == 0;
+                return dna;
+            }
         }
 
+        self.analyze_internal(pixels, width, height)
+    }
+
+    /// Core analysis pass over a validated RGBA buffer
+    fn analyze_internal(&self, pixels: &[u8], width: u32, height: u32) -> MaterialDNA {
         // Simple analysis for MVP
         let mut color_counts = HashMap::new();
         let mut total_pixels = 0u32;
+        let mut total_weight = 0.0f64;
         let mut min_x = width;
         let mut min_y = height;
         let mut max_x = 0u32;
         let mut max_y = 0u32;
+        let linear_lut = self.linearize.then(srgb_to_linear_lut);
         
         // Process pixels in chunks of 4 (RGBA)
         for (i, chunk) in pixels.chunks_exact(4).enumerate() {
@@ -83,23 +2797,37 @@ impl MaterialTriageEngine {
                 max_x = max_x.max(x);
                 max_y = max_y.max(y);
                 
-                // Classify color
-                let color_class = self.classify_color(r, g, b);
-                *color_counts.entry(color_class).or_insert(0) += 1;
+                // Dark outline pixels would otherwise all read as "stone"
+                if self.ignore_outline && self.luminance(r, g, b) <= self.outline_luma_max as f32 {
+                    continue;
+                }
+                
+                // Classify color - faint pixels count less when weighting by alpha
+                let weight = if self.weight_by_alpha { a as f64 / 255.0 } else { 1.0 };
+                total_weight += weight;
+                let color_class = match &linear_lut {
+                    Some(lut) => self.classify_color(lut[r as usize], lut[g as usize], lut[b as usize]),
+                    None => self.classify_color(r, g, b),
+                };
+                *color_counts.entry(color_class).or_insert(0.0) += weight;
             }
         }
         
         // Convert to percentages
         let mut color_profile = HashMap::new();
-        if total_pixels > 0 {
-            for (color, count) in color_counts {
-                color_profile.insert(color, count as f64 / total_pixels as f64);
+        if total_weight > 0.0 {
+            for (color, weight) in color_counts {
+                color_profile.insert(color, weight / total_weight);
             }
         }
         
-        // Calculate bounding box
-        let bbox_width = if max_x >= min_x { max_x - min_x + 1 } else { 0 };
-        let bbox_height = if max_y >= min_y { max_y - min_y + 1 } else { 0 };
+        // Calculate bounding box - an empty sprite has no content box at all
+        let is_empty = total_pixels == 0;
+        let alpha_bounding_box = if is_empty {
+            (0, 0, 0, 0)
+        } else {
+            (min_x, min_y, max_x - min_x + 1, max_y - min_y + 1)
+        };
         
         // Determine material type
         let material_type = self.classify_material(&color_profile);
@@ -121,8 +2849,8 @@ impl MaterialTriageEngine {
         let edge_density = 0.1;
         let is_object = edge_density > self.edge_threshold;
         
-        Ok(MaterialDNA {
-            alpha_bounding_box: (min_x, min_y, bbox_width, bbox_height),
+        MaterialDNA {
+            alpha_bounding_box,
             material_type,
             confidence,
             color_profile,
@@ -130,17 +2858,14 @@ impl MaterialTriageEngine {
             is_object,
             dominant_color,
             transparency_ratio,
-        })
-    }
-
-    /// Get Alpha-Bounding Box (ABB)
-    fn get_alpha_bounding_box(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<(u32, u32, u32, u32)> {
-        if pixels.len() != (width * height * 4) as usize {
-            return Err(pyo3::exceptions::PyValueError::new_err(
-                "Pixel data length doesn't match dimensions"
-            ));
+            is_empty,
         }
+    }
 
+    /// Calculate Alpha-Bounding Box (ABB) - Tight bounding box of non-transparent pixels
+    ///
+    /// Returns (0, 0, 0, 0) when the sprite is fully transparent.
+    fn calculate_alpha_bounding_box(&self, pixels: &[u8], width: u32, height: u32) -> (u32, u32, u32, u32) {
         let mut min_x = width;
         let mut min_y = height;
         let mut max_x = 0u32;
@@ -159,14 +2884,13 @@ impl MaterialTriageEngine {
             }
         }
         
-        let bbox_width = if max_x >= min_x { max_x - min_x + 1 } else { 0 };
-        let bbox_height = if max_y >= min_y { max_y - min_y + 1 } else { 0 };
+        if max_x < min_x || max_y < min_y {
+            return (0, 0, 0, 0);
+        }
         
-        Ok((min_x, min_y, bbox_width, bbox_height))
+        (min_x, min_y, max_x - min_x + 1, max_y - min_y + 1)
     }
-}
 
-impl MaterialTriageEngine {
     /// Classify individual pixel color
     fn classify_color(&self, r: u8, g: u8, b: u8) -> String {
         // Wood detection (Brown range)
@@ -205,11 +2929,22 @@ impl MaterialTriageEngine {
             }
         }
         
+        // Mostly unclassifiable colors is not a material
+        if material_type == "other" {
+            material_type = "unknown";
+        }
+        
         material_type.to_string()
     }
 
     /// Calculate confidence in material classification
     fn calculate_confidence(&self, color_profile: &HashMap<String, f64>, material_type: &str) -> f64 {
+        // "other" won: only the classified remainder counts, capped low
+        if material_type == "unknown" && !color_profile.is_empty() {
+            let other_ratio = color_profile.get("other").copied().unwrap_or(0.0);
+            return (1.0 - other_ratio).clamp(0.0, 0.3);
+        }
+        
         if let Some(ratio) = color_profile.get(material_type) {
             // Base confidence from dominant color ratio
             let base_confidence = *ratio;
@@ -253,6 +2988,896 @@ impl MaterialTriageEngine {
             (0, 0, 0)
         }
     }
+
+    /// Scanline flood fill - fills whole horizontal spans and seeds the rows above and below
+    #[allow(clippy::too_many_arguments)]
+    fn scanline_fill(
+        &self,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        x: u32,
+        y: u32,
+        fill: (u8, u8, u8, u8),
+        tolerance: u8,
+    ) -> Vec<u8> {
+        let w = width as usize;
+        let h = height as usize;
+        let seed_idx = (y as usize * w + x as usize) * 4;
+        let seed = [pixels[seed_idx], pixels[seed_idx + 1], pixels[seed_idx + 2], pixels[seed_idx + 3]];
+        let fill = [fill.0, fill.1, fill.2, fill.3];
+
+        // Matching is always against the original buffer, so a fill color that
+        // is itself within tolerance of the seed can't cause endless refilling
+        let matches = |i: usize| -> bool {
+            pixels[i * 4..i * 4 + 4]
+                .iter()
+                .zip(seed.iter())
+                .all(|(&p, &s)| p.abs_diff(s) <= tolerance)
+        };
+
+        let mut output = pixels.to_vec();
+        let mut visited = vec![false; w * h];
+        let mut stack = vec![(x as usize, y as usize)];
+
+        while let Some((sx, sy)) = stack.pop() {
+            let row = sy * w;
+            if visited[row + sx] || !matches(row + sx) {
+                continue;
+            }
+
+            // Extend the span left and right from the seed
+            let mut left = sx;
+            while left > 0 && !visited[row + left - 1] && matches(row + left - 1) {
+                left -= 1;
+            }
+            let mut right = sx;
+            while right + 1 < w && !visited[row + right + 1] && matches(row + right + 1) {
+                right += 1;
+            }
+
+            for px in left..=right {
+                visited[row + px] = true;
+                output[(row + px) * 4..(row + px) * 4 + 4].copy_from_slice(&fill);
+            }
+
+            // Push one seed per matching run in the neighbouring rows
+            for ny in [sy.wrapping_sub(1), sy + 1] {
+                if ny >= h {
+                    continue;
+                }
+                let neighbour_row = ny * w;
+                let mut in_run = false;
+                for px in left..=right {
+                    let idx = neighbour_row + px;
+                    if !visited[idx] && matches(idx) {
+                        if !in_run {
+                            stack.push((px, ny));
+                            in_run = true;
+                        }
+                    } else {
+                        in_run = false;
+                    }
+                }
+            }
+        }
+
+        output
+    }
+
+    /// Label connected opaque pixels using two-pass union-find
+    ///
+    /// Returns per-pixel labels (0 = transparent, components numbered from 1 in
+    /// row-major order of their first pixel) and the pixel count of each label.
+    fn label_components(&self, pixels: &[u8], width: u32, height: u32, connectivity: u8) -> (Vec<u32>, Vec<u32>) {
+        let mask: Vec<bool> = pixels.chunks_exact(4).map(|chunk| chunk[3] > 0).collect();
+        self.label_mask(&mask, width, height, connectivity)
+    }
+
+    /// Label connected `true` cells of a mask using two-pass union-find
+    fn label_mask(&self, mask: &[bool], width: u32, height: u32, connectivity: u8) -> (Vec<u32>, Vec<u32>) {
+        let w = width as usize;
+        let h = height as usize;
+        let mut labels = vec![0u32; w * h];
+        let mut parent: Vec<u32> = vec![0]; // index 0 is the background
+
+        fn find(parent: &mut [u32], mut label: u32) -> u32 {
+            while parent[label as usize] != label {
+                parent[label as usize] = parent[parent[label as usize] as usize];
+                label = parent[label as usize];
+            }
+            label
+        }
+
+        // First pass: provisional labels from already-visited neighbours
+        for y in 0..h {
+            for x in 0..w {
+                let idx = y * w + x;
+                if !mask[idx] {
+                    continue;
+                }
+
+                let mut neighbours = [0u32; 4];
+                if x > 0 {
+                    neighbours[0] = labels[idx - 1];
+                }
+                if y > 0 {
+                    neighbours[1] = labels[idx - w];
+                    if connectivity == 8 {
+                        if x > 0 {
+                            neighbours[2] = labels[idx - w - 1];
+                        }
+                        if x + 1 < w {
+                            neighbours[3] = labels[idx - w + 1];
+                        }
+                    }
+                }
+
+                let mut label = 0u32;
+                for &n in neighbours.iter().filter(|&&n| n != 0) {
+                    let root = find(&mut parent, n);
+                    if label == 0 {
+                        label = root;
+                    } else if root != label {
+                        let (keep, merge) = if root < label { (root, label) } else { (label, root) };
+                        parent[merge as usize] = keep;
+                        label = keep;
+                    }
+                }
+
+                if label == 0 {
+                    label = parent.len() as u32;
+                    parent.push(label);
+                }
+                labels[idx] = label;
+            }
+        }
+
+        // Second pass: resolve roots and compact to consecutive labels
+        let mut compact = vec![0u32; parent.len()];
+        let mut areas = Vec::new();
+        for label in labels.iter_mut().filter(|l| **l != 0) {
+            let root = find(&mut parent, *label) as usize;
+            if compact[root] == 0 {
+                areas.push(0);
+                compact[root] = areas.len() as u32;
+            }
+            *label = compact[root];
+            areas[*label as usize - 1] += 1;
+        }
+
+        (labels, areas)
+    }
+
+    /// Area-averaging resample with alpha-weighted color
+    ///
+    /// Each target pixel averages the source pixels it covers, weighted by the
+    /// covered fraction. Color is additionally weighted by alpha so fully
+    /// transparent pixels contribute coverage but no color.
+    fn box_resample(&self, pixels: &[u8], width: u32, height: u32, target_width: u32, target_height: u32) -> Vec<u8> {
+        // Per target column/row: (source index, covered fraction)
+        fn axis_weights(source: u32, target: u32) -> Vec<Vec<(usize, f64)>> {
+            let step = source as f64 / target as f64;
+            (0..target)
+                .map(|t| {
+                    let start = t as f64 * step;
+                    let end = start + step;
+                    let first = start.floor() as usize;
+                    let last = (end.ceil() as usize).min(source as usize);
+                    (first..last)
+                        .map(|s| (s, end.min(s as f64 + 1.0) - start.max(s as f64)))
+                        .filter(|&(_, w)| w > 0.0)
+                        .collect()
+                })
+                .collect()
+        }
+
+        let columns = axis_weights(width, target_width);
+        let rows = axis_weights(height, target_height);
+        let mut output = vec![0u8; target_width as usize * target_height as usize * 4];
+
+        for (ty, row_weights) in rows.iter().enumerate() {
+            for (tx, col_weights) in columns.iter().enumerate() {
+                let mut color = [0.0f64; 3];
+                let mut alpha_weight = 0.0;
+                let mut coverage = 0.0;
+
+                for &(sy, wy) in row_weights {
+                    for &(sx, wx) in col_weights {
+                        let idx = (sy * width as usize + sx) * 4;
+                        let weight = wx * wy;
+                        let a = pixels[idx + 3] as f64 * weight;
+                        for c in 0..3 {
+                            color[c] += pixels[idx + c] as f64 * a;
+                        }
+                        alpha_weight += a;
+                        coverage += weight;
+                    }
+                }
+
+                let out = (ty * target_width as usize + tx) * 4;
+                if alpha_weight > 0.0 {
+                    for c in 0..3 {
+                        output[out + c] = (color[c] / alpha_weight).round().min(255.0) as u8;
+                    }
+                }
+                if coverage > 0.0 {
+                    output[out + 3] = (alpha_weight / coverage).round().min(255.0) as u8;
+                }
+            }
+        }
+
+        output
+    }
+
+    /// Perceived luminance using the configured `luma_weights` (Rec. 601 by default)
+    fn luminance(&self, r: u8, g: u8, b: u8) -> f32 {
+        let (wr, wg, wb) = self.luma_weights;
+        // min() absorbs float rounding in the normalized weights
+        (wr * r as f32 + wg * g as f32 + wb * b as f32).min(255.0)
+    }
+
+    /// Per-pixel luminance in 0-255, with transparent pixels as 0
+    fn grayscale(&self, pixels: &[u8]) -> Vec<f32> {
+        pixels
+            .chunks_exact(4)
+            .map(|chunk| if chunk[3] > 0 { self.luminance(chunk[0], chunk[1], chunk[2]) } else { 0.0 })
+            .collect()
+    }
+
+    /// Mean position of opaque pixels, or None for an empty sprite
+    fn content_centroid(&self, pixels: &[u8], width: u32) -> Option<(f64, f64)> {
+        let mut sum_x = 0u64;
+        let mut sum_y = 0u64;
+        let mut count = 0u64;
+
+        for (i, chunk) in pixels.chunks_exact(4).enumerate() {
+            if chunk[3] > 0 {
+                sum_x += (i as u32 % width) as u64;
+                sum_y += (i as u32 / width) as u64;
+                count += 1;
+            }
+        }
+
+        if count > 0 {
+            Some((sum_x as f64 / count as f64, sum_y as f64 / count as f64))
+        } else {
+            None
+        }
+    }
+
+    /// Copy a rectangle that may extend past the image, padding with transparent pixels
+    #[allow(clippy::too_many_arguments)]
+    fn crop_padded(
+        &self,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        origin_x: i64,
+        origin_y: i64,
+        crop_width: u32,
+        crop_height: u32,
+    ) -> Vec<u8> {
+        let mut output = vec![0u8; (crop_width as usize) * (crop_height as usize) * 4];
+
+        for y in 0..crop_height as i64 {
+            let src_y = origin_y + y;
+            if src_y < 0 || src_y >= height as i64 {
+                continue;
+            }
+            for x in 0..crop_width as i64 {
+                let src_x = origin_x + x;
+                if src_x < 0 || src_x >= width as i64 {
+                    continue;
+                }
+                let src = ((src_y * width as i64 + src_x) * 4) as usize;
+                let dst = ((y * crop_width as i64 + x) * 4) as usize;
+                output[dst..dst + 4].copy_from_slice(&pixels[src..src + 4]);
+            }
+        }
+
+        output
+    }
+
+    /// Mark transparent pixels that cannot be reached from the image border
+    fn interior_hole_mask(&self, pixels: &[u8], width: u32, height: u32) -> Vec<bool> {
+        let w = width as usize;
+        let h = height as usize;
+        let transparent: Vec<bool> = pixels.chunks_exact(4).map(|chunk| chunk[3] == 0).collect();
+        let mut outside = vec![false; w * h];
+
+        // Seed the fill with every transparent border pixel
+        let mut stack: Vec<usize> = (0..w * h)
+            .filter(|&i| {
+                let (x, y) = (i % w, i / w);
+                transparent[i] && (x == 0 || y == 0 || x + 1 == w || y + 1 == h)
+            })
+            .collect();
+        for &i in &stack {
+            outside[i] = true;
+        }
+
+        while let Some(i) = stack.pop() {
+            let (x, y) = (i % w, i / w);
+            let mut visit = |n: usize| {
+                if transparent[n] && !outside[n] {
+                    outside[n] = true;
+                    stack.push(n);
+                }
+            };
+            if x > 0 {
+                visit(i - 1);
+            }
+            if x + 1 < w {
+                visit(i + 1);
+            }
+            if y > 0 {
+                visit(i - w);
+            }
+            if y + 1 < h {
+                visit(i + w);
+            }
+        }
+
+        transparent
+            .iter()
+            .zip(&outside)
+            .map(|(&t, &o)| t && !o)
+            .collect()
+    }
+
+    /// Bilinear sample at continuous pixel coordinates (pixel centers at integers)
+    ///
+    /// Samples outside the image count as transparent. Returns alpha-weighted
+    /// color sums and the interpolated alpha: ([r*a, g*a, b*a], a).
+    fn sample_bilinear(&self, pixels: &[u8], width: u32, height: u32, fx: f64, fy: f64) -> ([f64; 3], f64) {
+        let x0 = fx.floor();
+        let y0 = fy.floor();
+        let tx = fx - x0;
+        let ty = fy - y0;
+
+        let mut color = [0.0f64; 3];
+        let mut alpha = 0.0f64;
+        for (dx, dy, weight) in [
+            (0, 0, (1.0 - tx) * (1.0 - ty)),
+            (1, 0, tx * (1.0 - ty)),
+            (0, 1, (1.0 - tx) * ty),
+            (1, 1, tx * ty),
+        ] {
+            let sx = x0 as i64 + dx;
+            let sy = y0 as i64 + dy;
+            if weight == 0.0 || sx < 0 || sy < 0 || sx >= width as i64 || sy >= height as i64 {
+                continue;
+            }
+            let idx = ((sy * width as i64 + sx) * 4) as usize;
+            let a = pixels[idx + 3] as f64 * weight;
+            for c in 0..3 {
+                color[c] += pixels[idx + c] as f64 * a;
+            }
+            alpha += a;
+        }
+
+        (color, alpha)
+    }
+
+    /// Rotate counter-clockwise (on screen) into a canvas that fits the result
+    ///
+    /// Fails when the grown canvas is too large to address as RGBA bytes.
+    fn rotate_bilinear(&self, pixels: &[u8], width: u32, height: u32, degrees: f64) -> PyResult<(Vec<u8>, u32, u32)> {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        // Small epsilon keeps exact quarter turns from growing by a pixel
+        let out_width = ((width as f64 * cos.abs() + height as f64 * sin.abs()) - 1e-6).ceil().max(0.0) as u32;
+        let out_height = ((width as f64 * sin.abs() + height as f64 * cos.abs()) - 1e-6).ceil().max(0.0) as u32;
+
+        let src_cx = width as f64 / 2.0;
+        let src_cy = height as f64 / 2.0;
+        let dst_cx = out_width as f64 / 2.0;
+        let dst_cy = out_height as f64 / 2.0;
+
+        let mut output = vec![0u8; rgba_len(out_width, out_height)?];
+        for y in 0..out_height {
+            for x in 0..out_width {
+                // Inverse-map the destination pixel center into the source
+                let dx = x as f64 + 0.5 - dst_cx;
+                let dy = y as f64 + 0.5 - dst_cy;
+                let sx = dx * cos - dy * sin + src_cx - 0.5;
+                let sy = dx * sin + dy * cos + src_cy - 0.5;
+
+                let (color, alpha) = self.sample_bilinear(pixels, width, height, sx, sy);
+                if alpha <= 0.0 {
+                    continue;
+                }
+
+                let idx = ((y * out_width + x) * 4) as usize;
+                for c in 0..3 {
+                    output[idx + c] = (color[c] / alpha).round().clamp(0.0, 255.0) as u8;
+                }
+                output[idx + 3] = alpha.round().clamp(0.0, 255.0) as u8;
+            }
+        }
+
+        Ok((output, out_width, out_height))
+    }
+
+    /// HSV hue in degrees (0-360) and saturation (0-1); grays have hue 0
+    fn hue_saturation(&self, r: u8, g: u8, b: u8) -> (f64, f64) {
+        let (r, g, b) = (r as f64, g as f64, b as f64);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+        if delta == 0.0 {
+            return (0.0, 0.0);
+        }
+
+        let hue = if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+
+        (hue, delta / max)
+    }
+
+    /// Mean luminance of opaque pixels on (even, odd) rows, or None when
+    /// either parity has no opaque pixels
+    fn row_parity_luma(&self, pixels: &[u8], width: u32) -> Option<(f64, f64)> {
+        let mut sums = [0.0f64; 2];
+        let mut counts = [0u64; 2];
+        for (i, chunk) in pixels.chunks_exact(4).enumerate() {
+            if chunk[3] == 0 {
+                continue;
+            }
+            let parity = (i / width as usize) % 2;
+            sums[parity] += self.luminance(chunk[0], chunk[1], chunk[2]) as f64;
+            counts[parity] += 1;
+        }
+
+        if counts[0] == 0 || counts[1] == 0 {
+            return None;
+        }
+        Some((sums[0] / counts[0] as f64, sums[1] / counts[1] as f64))
+    }
+
+    /// Separable box blur of a single-channel field (zero outside the bounds)
+    fn box_blur(&self, values: &[f32], width: usize, height: usize, radius: usize) -> Vec<f32> {
+        if radius == 0 {
+            return values.to_vec();
+        }
+
+        let window = (2 * radius + 1) as f32;
+        let pass = |input: &[f32], len: usize, lines: usize, index: &dyn Fn(usize, usize) -> usize| {
+            let mut output = vec![0.0f32; input.len()];
+            for line in 0..lines {
+                // Running sum over [pos - radius, pos + radius]
+                let mut sum: f32 = (0..radius.min(len)).map(|pos| input[index(line, pos)]).sum();
+                for pos in 0..len {
+                    if pos + radius < len {
+                        sum += input[index(line, pos + radius)];
+                    }
+                    if pos > radius {
+                        sum -= input[index(line, pos - radius - 1)];
+                    }
+                    output[index(line, pos)] = sum / window;
+                }
+            }
+            output
+        };
+
+        let horizontal = pass(values, width, height, &|y, x| y * width + x);
+        pass(&horizontal, height, width, &|x, y| y * width + x)
+    }
+
+    /// Separable Gaussian blur (sigma = radius / 2) of a single-channel field,
+    /// zero outside the bounds
+    fn gaussian_blur(&self, values: &[f32], width: usize, height: usize, radius: usize) -> Vec<f32> {
+        if radius == 0 {
+            return values.to_vec();
+        }
+
+        let sigma = radius as f32 / 2.0;
+        let mut kernel: Vec<f32> = (0..=2 * radius)
+            .map(|i| {
+                let d = i as f32 - radius as f32;
+                (-d * d / (2.0 * sigma * sigma)).exp()
+            })
+            .collect();
+        let norm: f32 = kernel.iter().sum();
+        kernel.iter_mut().for_each(|k| *k /= norm);
+
+        let pass = |input: &[f32], len: usize, lines: usize, index: &dyn Fn(usize, usize) -> usize| {
+            let mut output = vec![0.0f32; input.len()];
+            for line in 0..lines {
+                for pos in 0..len {
+                    // Only taps that land inside the line contribute
+                    let first = pos.saturating_sub(radius);
+                    let last = (pos + radius).min(len - 1);
+                    let sum: f32 = (first..=last)
+                        .map(|src| input[index(line, src)] * kernel[src + radius - pos])
+                        .sum();
+                    output[index(line, pos)] = sum;
+                }
+            }
+            output
+        };
+
+        let horizontal = pass(values, width, height, &|y, x| y * width + x);
+        pass(&horizontal, height, width, &|x, y| y * width + x)
+    }
+
+    /// Indices of opaque pixels with a transparent 4-neighbour (the area
+    /// outside the image counts as transparent), in row-major order
+    fn boundary_pixels(&self, pixels: &[u8], width: u32, height: u32) -> Vec<usize> {
+        let alpha = |x: i64, y: i64| {
+            if x < 0 || y < 0 || x >= width as i64 || y >= height as i64 {
+                0
+            } else {
+                pixels[((y * width as i64 + x) * 4 + 3) as usize]
+            }
+        };
+
+        let mut boundary = Vec::new();
+        for y in 0..height as i64 {
+            for x in 0..width as i64 {
+                if alpha(x, y) == 0 {
+                    continue;
+                }
+                let neighbours = [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)];
+                if neighbours.iter().any(|&(nx, ny)| alpha(nx, ny) == 0) {
+                    boundary.push((y * width as i64 + x) as usize);
+                }
+            }
+        }
+        boundary
+    }
+
+    /// Mean per-pixel difference (channel-averaged RGBA) between opposite
+    /// edges: left/right columns and top/bottom rows. None for zero-area images
+    fn seam_difference(&self, pixels: &[u8], width: u32, height: u32) -> Option<f64> {
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        let pixel_difference = |a: (u32, u32), b: (u32, u32)| {
+            let ia = ((a.1 * width + a.0) * 4) as usize;
+            let ib = ((b.1 * width + b.0) * 4) as usize;
+            (0..4).map(|c| pixels[ia + c].abs_diff(pixels[ib + c]) as f64).sum::<f64>() / 4.0
+        };
+
+        let mut total = 0.0;
+        for y in 0..height {
+            total += pixel_difference((0, y), (width - 1, y));
+        }
+        for x in 0..width {
+            total += pixel_difference((x, 0), (x, height - 1));
+        }
+
+        Some(total / (width + height) as f64)
+    }
+
+    /// Fraction of pixels whose 3x3 Sobel magnitude (|gx| + |gy| over
+    /// luminance, transparent as black) exceeds 30; border pixels never count
+    fn sobel_edge_density(&self, pixels: &[u8], width: u32, height: u32) -> f64 {
+        if width < 3 || height < 3 {
+            return 0.0;
+        }
+
+        let gray = self.grayscale(pixels);
+        let at = |x: u32, y: u32| gray[(y * width + x) as usize];
+        let mut edges = 0u64;
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                let gx = at(x + 1, y - 1) + 2.0 * at(x + 1, y) + at(x + 1, y + 1)
+                    - at(x - 1, y - 1) - 2.0 * at(x - 1, y) - at(x - 1, y + 1);
+                let gy = at(x - 1, y + 1) + 2.0 * at(x, y + 1) + at(x + 1, y + 1)
+                    - at(x - 1, y - 1) - 2.0 * at(x, y - 1) - at(x + 1, y - 1);
+                if gx.abs() + gy.abs() > 30.0 {
+                    edges += 1;
+                }
+            }
+        }
+
+        edges as f64 / (width as u64 * height as u64) as f64
+    }
+
+    /// Second-moment ellipse (center_x, center_y, semi_major, semi_minor,
+    /// angle) as documented on `bounding_ellipse`; None for empty sprites
+    fn ellipse_fit(&self, pixels: &[u8], width: u32) -> Option<(f64, f64, f64, f64, f64)> {
+        let (cx, cy) = self.content_centroid(pixels, width)?;
+
+        let mut sxx = 0.0;
+        let mut syy = 0.0;
+        let mut sxy = 0.0;
+        let mut count = 0.0;
+        for (i, chunk) in pixels.chunks_exact(4).enumerate() {
+            if chunk[3] == 0 {
+                continue;
+            }
+            let dx = (i as u32 % width) as f64 - cx;
+            let dy = (i as u32 / width) as f64 - cy;
+            sxx += dx * dx;
+            syy += dy * dy;
+            sxy += dx * dy;
+            count += 1.0;
+        }
+        // Each pixel is a unit square, not a point: add its own variance
+        let var_x = sxx / count + 1.0 / 12.0;
+        let var_y = syy / count + 1.0 / 12.0;
+        let cov = sxy / count;
+
+        let mean = (var_x + var_y) / 2.0;
+        let spread = (((var_x - var_y) / 2.0).powi(2) + cov * cov).sqrt();
+        let major = 2.0 * (mean + spread).sqrt();
+        let minor = 2.0 * (mean - spread).max(0.0).sqrt();
+
+        // Screen y points down, so negate to report counter-clockwise
+        let mut angle = -0.5 * (2.0 * cov).atan2(var_x - var_y).to_degrees();
+        if angle <= -90.0 {
+            angle += 180.0;
+        }
+
+        // `+ 0.0` turns the -0.0 of an axis-aligned fit into 0.0
+        Some((cx + 0.5, cy + 0.5, major, minor, angle + 0.0))
+    }
+}
+
+/// Fraction of pixels that changed beyond `tolerance` between two frames
+///
+/// A pixel counts as changed when any RGBA channel differs by more than
+/// `tolerance`. Values near 0.0 indicate a duplicate frame.
+#[pyfunction]
+fn compare_frames(prev: &[u8], curr: &[u8], width: u32, height: u32, tolerance: u8) -> PyResult<f64> {
+    validate_rgba(prev, width, height)?;
+    validate_rgba(curr, width, height)?;
+
+    let total_pixels = (width * height) as usize;
+    if total_pixels == 0 {
+        return Ok(0.0);
+    }
+
+    let changed = run_in_pool(|| {
+        prev.par_chunks_exact(4)
+            .zip(curr.par_chunks_exact(4))
+            .filter(|(a, b)| a.iter().zip(b.iter()).any(|(&x, &y)| x.abs_diff(y) > tolerance))
+            .count()
+    });
+
+    Ok(changed as f64 / total_pixels as f64)
+}
+
+/// Merge per-tile color profiles into one sheet-wide profile
+///
+/// Each profile is paired with its opaque pixel count and contributes in
+/// proportion to it, matching what `analyze_sprite` would report for the
+/// whole sheet. Returns an empty profile when the total weight is 0.
+#[pyfunction]
+fn merge_color_profiles(profiles: Vec<(HashMap<String, f64>, u32)>) -> HashMap<String, f64> {
+    let total_weight: f64 = profiles.iter().map(|(_, weight)| *weight as f64).sum();
+    let mut merged = HashMap::new();
+    if total_weight == 0.0 {
+        return merged;
+    }
+
+    for (profile, weight) in profiles {
+        for (color, ratio) in profile {
+            *merged.entry(color).or_insert(0.0) += ratio * weight as f64 / total_weight;
+        }
+    }
+
+    merged
+}
+
+/// Exact byte equality of two sprite buffers
+///
+/// Cheap pre-check before perceptual comparison. Returns false immediately on
+/// a length mismatch; otherwise slice equality compiles to `memcmp`, which
+/// already compares word-at-a-time.
+#[pyfunction]
+fn sprites_equal(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a == b
+}
+
+/// All-pairs Hamming distance matrix for a batch of 64-bit perceptual hashes
+///
+/// Row `i`, column `j` is the number of differing bits between `hashes[i]`
+/// and `hashes[j]`; the matrix is symmetric with a zero diagonal. Rows are
+/// computed in parallel.
+#[pyfunction]
+fn similarity_matrix(hashes: Vec<u64>) -> PyResult<Vec<Vec<u32>>> {
+    let matrix = run_in_pool(|| {
+        hashes
+            .par_iter()
+            .map(|&a| hashes.iter().map(|&b| (a ^ b).count_ones()).collect())
+            .collect()
+    });
+
+    Ok(matrix)
+}
+
+/// Whether both dimensions meet texture atlas constraints
+///
+/// Each side must be a multiple of `multiple_of` (use 1 for no constraint)
+/// and, with `require_pow2`, a power of two. Zero-sized sides never pass
+/// the power-of-two check.
+#[pyfunction]
+fn check_dimensions(width: u32, height: u32, multiple_of: u32, require_pow2: bool) -> PyResult<bool> {
+    if multiple_of == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "multiple_of must be greater than 0 (use 1 for no constraint)"
+        ));
+    }
+
+    let fits = |side: u32| side.is_multiple_of(multiple_of) && (!require_pow2 || side.is_power_of_two());
+    Ok(fits(width) && fits(height))
+}
+
+/// Alpha-weighted mean of a batch of aligned, same-sized sprites
+///
+/// RGB is weighted by alpha so transparent pixels don't darken the mean;
+/// alpha is a plain average. Pixels transparent in every input come out as
+/// (0, 0, 0, 0).
+#[pyfunction]
+fn average_sprites(sprites: Vec<Vec<u8>>, width: u32, height: u32) -> PyResult<Vec<u8>> {
+    if sprites.is_empty() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "At least one sprite is required"
+        ));
+    }
+    for sprite in &sprites {
+        validate_rgba(sprite, width, height)?;
+    }
+
+    let count = sprites.len() as u64;
+    let mut output = vec![0u8; sprites[0].len()];
+    for (i, out) in output.chunks_exact_mut(4).enumerate() {
+        let mut rgb = [0u64; 3];
+        let mut alpha_sum = 0u64;
+        for sprite in &sprites {
+            let alpha = sprite[i * 4 + 3] as u64;
+            for c in 0..3 {
+                rgb[c] += sprite[i * 4 + c] as u64 * alpha;
+            }
+            alpha_sum += alpha;
+        }
+        if alpha_sum == 0 {
+            continue;
+        }
+        for c in 0..3 {
+            out[c] = ((rgb[c] + alpha_sum / 2) / alpha_sum) as u8;
+        }
+        out[3] = ((alpha_sum + count / 2) / count) as u8;
+    }
+
+    Ok(output)
+}
+
+/// Whether `a` is the left-to-right mirror image of `b`
+///
+/// Every RGBA channel must match its mirrored counterpart within
+/// `tolerance`; pixels transparent in both count as equal whatever their RGB.
+/// Both buffers must have the given dimensions.
+#[pyfunction]
+fn is_mirror_of(a: &[u8], b: &[u8], width: u32, height: u32, tolerance: u8) -> PyResult<bool> {
+    validate_rgba(a, width, height)?;
+    validate_rgba(b, width, height)?;
+
+    let flipped = flip_horizontal(b, width);
+    Ok(a.chunks_exact(4).zip(flipped.chunks_exact(4)).all(|(pa, pb)| {
+        (pa[3] == 0 && pb[3] == 0) || pa.iter().zip(pb).all(|(&x, &y)| x.abs_diff(y) <= tolerance)
+    }))
+}
+
+/// Set the number of worker threads used by parallel methods
+///
+/// Useful when the calling Python process already fans out across cores.
+/// A value of 0 restores rayon's default global pool.
+#[pyfunction]
+fn set_thread_count(n: usize) -> PyResult<()> {
+    let pool = if n == 0 {
+        None
+    } else {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(n)
+            .build()
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(
+                format!("Failed to build thread pool: {}", e)
+            ))?;
+        Some(Arc::new(pool))
+    };
+
+    *THREAD_POOL.write().unwrap_or_else(|e| e.into_inner()) = pool;
+    Ok(())
+}
+
+/// Run a parallel operation inside the configured thread pool
+fn run_in_pool<R: Send>(op: impl FnOnce() -> R + Send) -> R {
+    let pool = THREAD_POOL.read().unwrap_or_else(|e| e.into_inner()).clone();
+    match pool {
+        Some(pool) => pool.install(op),
+        None => op(),
+    }
+}
+
+/// Number of RGBA samples for the given dimensions
+///
+/// Checked so pathological dimensions raise instead of wrapping into a
+/// false-passing length check. Keeping the total within u32 also keeps the
+/// `(y * width + x) * 4` index arithmetic used throughout from overflowing.
+fn rgba_len(width: u32, height: u32) -> PyResult<usize> {
+    width
+        .checked_mul(height)
+        .and_then(|pixels| pixels.checked_mul(4))
+        .map(|len| len as usize)
+        .ok_or_else(|| pyo3::exceptions::PyValueError::new_err(
+            "Image dimensions are too large"
+        ))
+}
+
+/// Validate that an RGBA buffer matches the given dimensions
+fn validate_rgba(pixels: &[u8], width: u32, height: u32) -> PyResult<()> {
+    if pixels.len() != rgba_len(width, height)? {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Pixel data length doesn't match dimensions"
+        ));
+    }
+
+    Ok(())
+}
+
+/// Validate a component connectivity value (4 or 8)
+fn validate_connectivity(connectivity: u8) -> PyResult<()> {
+    if connectivity != 4 && connectivity != 8 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Connectivity must be 4 or 8"
+        ));
+    }
+
+    Ok(())
+}
+
+/// 64-bit FNV-1a hash - fast, non-cryptographic content key
+fn fnv1a_hash(data: &[u8]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    for &byte in data {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+/// SipHash of the bytes with fixed keys, independent of fnv1a_hash
+fn sip_hash(data: &[u8]) -> u64 {
+    use std::hash::Hasher;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    hasher.write(data);
+    hasher.finish()
+}
+
+/// sRGB decoding curve as a byte-to-byte table (linear light rescaled to 0-255)
+///
+/// Classification thresholds are tuned for gamma-encoded bytes, so ratios shift
+/// noticeably when enabled: dark browns drop well below the wood range.
+fn srgb_to_linear_lut() -> [u8; 256] {
+    let mut lut = [0u8; 256];
+    for (value, out) in lut.iter_mut().enumerate() {
+        let c = value as f64 / 255.0;
+        let linear = if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) };
+        *out = (linear * 255.0).round() as u8;
+    }
+    lut
+}
+
+/// Mirror an RGBA buffer left-to-right
+fn flip_horizontal(pixels: &[u8], width: u32) -> Vec<u8> {
+    let row_len = width as usize * 4;
+    let mut output = Vec::with_capacity(pixels.len());
+    for row in pixels.chunks_exact(row_len.max(1)) {
+        for pixel in row.chunks_exact(4).rev() {
+            output.extend_from_slice(pixel);
+        }
+    }
+    output
 }
 
 /// Python module definition
@@ -260,6 +3885,14 @@ impl MaterialTriageEngine {
 fn dgt_harvest_rust(_py: Python, m: &Bound<PyModule>) -> PyResult<()> {
     m.add_class::<MaterialTriageEngine>()?;
     m.add_class::<MaterialDNA>()?;
+    m.add_function(wrap_pyfunction!(compare_frames, m)?)?;
+    m.add_function(wrap_pyfunction!(set_thread_count, m)?)?;
+    m.add_function(wrap_pyfunction!(merge_color_profiles, m)?)?;
+    m.add_function(wrap_pyfunction!(sprites_equal, m)?)?;
+    m.add_function(wrap_pyfunction!(similarity_matrix, m)?)?;
+    m.add_function(wrap_pyfunction!(check_dimensions, m)?)?;
+    m.add_function(wrap_pyfunction!(average_sprites, m)?)?;
+    m.add_function(wrap_pyfunction!(is_mirror_of, m)?)?;
     
     Ok(())
 }
@@ -65,6 +65,15 @@ impl MaterialType {
     }
 }
 
+/// Per-channel half-width of the wood band around `wood_threshold`
+const WOOD_BAND: (u8, u8, u8) = (25, 25, 20);
+/// Maximum channel-spread deviation from `stone_threshold`'s tint
+const STONE_MAX_VARIANCE: i32 = 30;
+/// Grass green must exceed `grass_threshold` green minus this margin
+const GRASS_GREEN_MARGIN: u8 = 50;
+/// Water blue must exceed `water_threshold` blue minus this margin
+const WATER_BLUE_MARGIN: u8 = 30;
+
 /// High-performance Material Triage Engine
 #[pyclass]
 struct MaterialTriageEngine {
@@ -73,30 +82,72 @@ struct MaterialTriageEngine {
     grass_threshold: (u8, u8, u8), // RGB ranges for grass
     water_threshold: (u8, u8, u8), // RGB ranges for water
     edge_threshold: f64, // Edge density threshold for object vs texture
+    edge_operator: String, // "sobel" or "laplacian"
+    luma_weights: (f32, f32, f32), // Grayscale weights, normalized to sum to 1
 }
 
 #[pymethods]
 impl MaterialTriageEngine {
     #[new]
-    fn new() -> Self {
-        Self {
-            // Wood: High Brown (R: 100-150, G: 50-100, B: 20-60)
-            wood_threshold: (125, 75, 40),
-            // Stone: Gray Neutral (R ≈ G ≈ B)
-            stone_threshold: (128, 128, 128),
-            // Grass: High Green (G > R & B)
-            grass_threshold: (80, 150, 60),
-            // Water: High Blue (B > 150)
-            water_threshold: (60, 80, 180),
-            edge_threshold: 0.2, // 20% edge density threshold
+    #[pyo3(signature = (
+        // Wood: High Brown (R: 100-150, G: 50-100, B: 20-60)
+        wood_threshold = (125, 75, 40),
+        // Stone: Gray Neutral (R ≈ G ≈ B)
+        stone_threshold = (128, 128, 128),
+        // Grass: High Green (G > R & B, G > 100)
+        grass_threshold = (80, 150, 60),
+        // Water: High Blue (B > 150)
+        water_threshold = (60, 80, 180),
+        // Edge kernel: "sobel" or "laplacian" (better for thin lines)
+        edge_operator = "sobel",
+        // Grayscale weights: Rec.601 by default, e.g. (0.2126, 0.7152, 0.0722) for Rec.709
+        luma_weights = (0.299, 0.587, 0.114),
+    ))]
+    fn new(
+        wood_threshold: (u8, u8, u8),
+        stone_threshold: (u8, u8, u8),
+        grass_threshold: (u8, u8, u8),
+        water_threshold: (u8, u8, u8),
+        edge_operator: &str,
+        luma_weights: (f32, f32, f32),
+    ) -> PyResult<Self> {
+        if edge_operator != "sobel" && edge_operator != "laplacian" {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "edge_operator must be 'sobel' or 'laplacian'"
+            ));
+        }
+
+        let (wr, wg, wb) = luma_weights;
+        let weight_sum = wr + wg + wb;
+        if wr < 0.0 || wg < 0.0 || wb < 0.0 || !weight_sum.is_finite() || weight_sum <= 0.0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "luma_weights must be non-negative with a positive sum"
+            ));
         }
+
+        Ok(Self {
+            wood_threshold,
+            stone_threshold,
+            grass_threshold,
+            water_threshold,
+            edge_threshold: 0.2, // 20% edge density threshold
+            edge_operator: edge_operator.to_string(),
+            // Normalize so grayscale stays within 0-255
+            luma_weights: (wr / weight_sum, wg / weight_sum, wb / weight_sum),
+        })
     }
 
     /// Complete Material Triage Analysis
     fn analyze_sprite<'a>(&self, py: Python<'a>, pixels: &'a PyBytes, width: u32, height: u32) -> PyResult<MaterialDNA> {
         let pixels_data = unsafe { std::slice::from_raw_parts(pixels.as_ptr(), pixels.len()?) };
         
-        if pixels_data.len() != (width * height * 4) as usize {
+        let expected_len = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(4))
+            .ok_or_else(|| pyo3::exceptions::PyValueError::new_err(
+                "Image dimensions are too large"
+            ))?;
+        if pixels_data.len() != expected_len as usize {
             return Err(pyo3::exceptions::PyValueError::new_err(
                 "Pixel data length doesn't match dimensions"
             ));
@@ -240,28 +291,37 @@ impl MaterialTriageEngine {
     }
 
     /// Classify individual pixel color
+    ///
+    /// The material thresholds set in the constructor drive each test, and
+    /// the defaults match the original fixed ranges.
     fn classify_color(&self, r: u8, g: u8, b: u8) -> String {
-        // Wood detection (Brown range)
-        if (100 <= r && r <= 150) && (50 <= g && g <= 100) && (20 <= b && b <= 60) {
+        // Wood detection (Brown band around wood_threshold)
+        let (wood_r, wood_g, wood_b) = self.wood_threshold;
+        if r.abs_diff(wood_r) <= WOOD_BAND.0 && g.abs_diff(wood_g) <= WOOD_BAND.1 && b.abs_diff(wood_b) <= WOOD_BAND.2 {
             return "wood".to_string();
         }
         
-        // Stone detection (Gray range)
-        let gray_variance = ((r as i32 - g as i32).abs() + (g as i32 - b as i32).abs()) as u8;
-        if gray_variance < 30 {
+        // Stone detection (Channel spread close to stone_threshold's tint)
+        let (stone_r, stone_g, stone_b) = self.stone_threshold;
+        let stone_variance = ((r as i32 - g as i32) - (stone_r as i32 - stone_g as i32)).abs()
+            + ((g as i32 - b as i32) - (stone_g as i32 - stone_b as i32)).abs();
+        if stone_variance < STONE_MAX_VARIANCE {
             return "stone".to_string();
         }
         
         // Grass detection (Green dominant)
-        if g > r && g > b && g > 100 {
+        if g > r && g > b && g > self.grass_threshold.1.saturating_sub(GRASS_GREEN_MARGIN) {
             return "grass".to_string();
         }
         
         // Water detection (Blue dominant)
-        if b > 150 && b > r && b > g {
+        if b > self.water_threshold.2.saturating_sub(WATER_BLUE_MARGIN) && b > r && b > g {
             return "water".to_string();
         }
         
+        // Gray variance for the remaining heuristics
+        let gray_variance = ((r as i32 - g as i32).abs() + (g as i32 - b as i32).abs()) as u8;
+        
         // Metal detection (High contrast, metallic)
         if (r > 200 || g > 200 || b > 200) && gray_variance > 50 {
             return "metal".to_string();
@@ -292,14 +352,15 @@ impl MaterialTriageEngine {
             let a = chunk[3];
             
             if a > 0 {
-                // Convert to grayscale using luminance formula
-                gray_pixels[i] = (0.299 * r + 0.587 * g + 0.114 * b) as u8;
+                // Convert to grayscale using the configured luma weights
+                let (wr, wg, wb) = self.luma_weights;
+                gray_pixels[i] = (wr * r + wg * g + wb * b) as u8;
             } else {
                 gray_pixels[i] = 0;
             }
         }
         
-        // Simple edge detection using Sobel operator
+        // Simple edge detection using the configured 3x3 operator
         let mut edge_pixels = vec![0u8; (width * height) as usize];
         let mut edge_count = 0u32;
         
@@ -317,12 +378,16 @@ impl MaterialTriageEngine {
                 let bm = gray_pixels[((y+1) * width + x) as usize] as i32;
                 let br = gray_pixels[((y+1) * width + (x+1)) as usize] as i32;
                 
-                // Sobel X and Y
-                let sobel_x = (-tl + tr - 2*ml + 2*mr - bl + br).abs();
-                let sobel_y = (-tl - 2*tm - tr + bl + 2*bm + br).abs();
-                
-                // Edge magnitude
-                let edge_magnitude = (sobel_x + sobel_y) as u8;
+                let edge_magnitude = if self.edge_operator == "laplacian" {
+                    // Laplacian: 4-neighbour sum minus 4x centre
+                    let center = gray_pixels[idx] as i32;
+                    (tm + ml + mr + bm - 4*center).abs()
+                } else {
+                    // Sobel X and Y
+                    let sobel_x = (-tl + tr - 2*ml + 2*mr - bl + br).abs();
+                    let sobel_y = (-tl - 2*tm - tr + bl + 2*bm + br).abs();
+                    sobel_x + sobel_y
+                };
                 
                 if edge_magnitude > 30 { // Threshold for edge detection
                     edge_pixels[idx] = 255;
@@ -461,3 +526,29 @@ fn dgt_harvest_rust(_py: Python, m: &Bound<PyModule>) -> PyResult<()> {
     
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine_with_wood(wood_threshold: (u8, u8, u8)) -> MaterialTriageEngine {
+        MaterialTriageEngine::new(
+            wood_threshold,
+            (128, 128, 128),
+            (80, 150, 60),
+            (60, 80, 180),
+            "sobel",
+            (0.299, 0.587, 0.114),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn shifted_wood_threshold_reclassifies_borderline_pixel() {
+        // R is 30 above the default wood center, just outside the ±25 band
+        let (r, g, b) = (155, 75, 40);
+
+        assert_ne!(engine_with_wood((125, 75, 40)).classify_color(r, g, b), "wood");
+        assert_eq!(engine_with_wood((135, 75, 40)).classify_color(r, g, b), "wood");
+    }
+}
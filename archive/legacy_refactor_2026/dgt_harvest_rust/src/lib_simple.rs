@@ -50,7 +50,13 @@ impl MaterialTriageEngine {
 
     /// Complete Material Triage Analysis
     fn analyze_sprite(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<MaterialDNA> {
-        if pixels.len() != (width * height * 4) as usize {
+        let expected_len = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(4))
+            .ok_or_else(|| pyo3::exceptions::PyValueError::new_err(
+                "Image dimensions are too large"
+            ))?;
+        if pixels.len() != expected_len as usize {
             return Err(pyo3::exceptions::PyValueError::new_err(
                 "Pixel data length doesn't match dimensions"
             ));
@@ -135,7 +141,13 @@ impl MaterialTriageEngine {
 
     /// Get Alpha-Bounding Box (ABB)
     fn get_alpha_bounding_box(&self, pixels: &[u8], width: u32, height: u32) -> PyResult<(u32, u32, u32, u32)> {
-        if pixels.len() != (width * height * 4) as usize {
+        let expected_len = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(4))
+            .ok_or_else(|| pyo3::exceptions::PyValueError::new_err(
+                "Image dimensions are too large"
+            ))?;
+        if pixels.len() != expected_len as usize {
             return Err(pyo3::exceptions::PyValueError::new_err(
                 "Pixel data length doesn't match dimensions"
             ));
@@ -205,11 +217,22 @@ impl MaterialTriageEngine {
             }
         }
         
+        // Mostly unclassifiable colors is not a material
+        if material_type == "other" {
+            material_type = "unknown";
+        }
+        
         material_type.to_string()
     }
 
     /// Calculate confidence in material classification
     fn calculate_confidence(&self, color_profile: &HashMap<String, f64>, material_type: &str) -> f64 {
+        // "other" won: only the classified remainder counts, capped low
+        if material_type == "unknown" && !color_profile.is_empty() {
+            let other_ratio = color_profile.get("other").copied().unwrap_or(0.0);
+            return (1.0 - other_ratio).clamp(0.0, 0.3);
+        }
+        
         if let Some(ratio) = color_profile.get(material_type) {
             // Base confidence from dominant color ratio
             let base_confidence = *ratio;
@@ -5,7 +5,7 @@ High-performance intelligent asset analysis with Rust backend
 
 import sys
 from pathlib import Path
-from typing import Tuple, Optional, Dict, Any
+from typing import Tuple, Optional, Dict, Any, List
 from loguru import logger
 
 # Try to import the compiled Rust module using PyO3/maturin
@@ -22,13 +22,44 @@ except ImportError as e:
 class RustSpriteScanner:
     """High-performance sprite scanner with Rust Material Triage Engine"""
     
+    # Chest color bands as ((r_min, r_max), (g_min, g_max), b_max)
+    CHEST_COLOR_RANGES = (
+        ((80, 180), (40, 140), 80),     # Brown wood
+        ((160, 255), (100, 200), 100),  # Gold trim
+    )
+    
     def __init__(self, chest_threshold: float = 0.3, green_threshold: float = 0.2, 
-                 gray_threshold: float = 0.3, diversity_threshold: float = 0.05):
+                 gray_threshold: float = 0.3, diversity_threshold: float = 0.05,
+                 character_min_pixels: int = 20,
+                 character_aspect_range: Tuple[float, float] = (0.5, 2.0),
+                 character_min_colors: int = 3,
+                 compute_diversity: bool = True):
+        """Configure the classification thresholds
+        
+        The character_* knobs only tune the Python fallback's is_character
+        check. The Rust engine derives is_character from its own object and
+        material classification and ignores them.
+        
+        compute_diversity=False skips the fallback's per-color tally for bulk
+        ingestion. color_diversity is then 0.0, is_material is False and
+        is_character is False, because the character check cannot be made
+        without a color count; decoration falls back to the green/gray ratios.
+        The Rust engine keeps no per-color tally, so on that path the flag only
+        zeroes color_diversity and makes nothing faster.
+        """
         self.chest_threshold = chest_threshold
         self.green_threshold = green_threshold
         self.gray_threshold = gray_threshold
         self.diversity_threshold = diversity_threshold
         
+        # is_character heuristics: opaque pixels, width/height bounds, distinct colors
+        self.character_min_pixels = character_min_pixels
+        self.character_aspect_range = character_aspect_range
+        self.character_min_colors = character_min_colors
+        
+        # Fallback only: skip the per-color tally (see above for what degrades)
+        self.compute_diversity = compute_diversity
+        
         # Initialize Rust Material Triage Engine if available
         self.rust_engine: Optional[dgt_harvest_rust.MaterialTriageEngine] = None
         if RUST_AVAILABLE:
@@ -60,7 +91,7 @@ class RustSpriteScanner:
                 'chest_probability': 0.0,  # Will be calculated from material type
                 'is_chest': False,        # Will be calculated from material type
                 'content_bounds': material_dna.alpha_bounding_box,
-                'color_diversity': len(material_dna.color_profile) * 0.01,  # Approximate
+                'color_diversity': len(material_dna.color_profile) * 0.01 if self.compute_diversity else 0.0,  # Approximate
                 'green_ratio': material_dna.color_profile.get('grass', 0.0),
                 'gray_ratio': material_dna.color_profile.get('stone', 0.0),
                 'brown_gold_ratio': material_dna.color_profile.get('wood', 0.0),
@@ -84,6 +115,7 @@ class RustSpriteScanner:
                 analysis['chest_probability'] = material_dna.color_profile.get('wood', 0.0) * 0.8
                 analysis['is_chest'] = analysis['chest_probability'] > self.chest_threshold
             
+            analysis['category'] = self._resolve_category(analysis)
             return analysis
             
         except Exception as e:
@@ -112,12 +144,12 @@ class RustSpriteScanner:
                 total_pixels += 1
                 
                 # Track colors
-                color_key = (r, g, b)
-                colors[color_key] = colors.get(color_key, 0) + 1
+                if self.compute_diversity:
+                    color_key = (r, g, b)
+                    colors[color_key] = colors.get(color_key, 0) + 1
                 
                 # Chest detection (brown/gold)
-                if (80 <= r <= 180 and 40 <= g <= 140 and b <= 80) or \
-                   (160 <= r <= 255 and 100 <= g <= 200 and b <= 100):
+                if any(self._in_chest_range(r, g, b, band) for band in self.CHEST_COLOR_RANGES):
                     brown_gold_pixels += 1
                 
                 # Plant detection
@@ -136,14 +168,23 @@ class RustSpriteScanner:
         
         # Determine object type
         aspect_ratio = width / height
-        is_character = total_pixels > 20 and 0.5 <= aspect_ratio <= 2.0 and len(colors) > 3
-        is_decoration = color_diversity > 0.05 or green_ratio > 0.2 or gray_ratio > 0.3
-        is_material = color_diversity < 0.1
+        min_aspect, max_aspect = self.character_aspect_range
+        is_character = (total_pixels > self.character_min_pixels
+                        and min_aspect <= aspect_ratio <= max_aspect)
+        is_decoration = green_ratio > 0.2 or gray_ratio > 0.3
+        if self.compute_diversity:
+            is_character = is_character and len(colors) > self.character_min_colors
+            is_decoration = is_decoration or color_diversity > 0.05
+            is_material = color_diversity < 0.1
+        else:
+            # Without a color count, only the ratio signals remain
+            is_character = False
+            is_material = False
         
         # Simple bounding box
         content_bounds = (0, 0, width, height)
         
-        return {
+        analysis = {
             'chest_probability': chest_probability,
             'is_chest': chest_probability > self.chest_threshold,
             'content_bounds': content_bounds,
@@ -165,6 +206,99 @@ class RustSpriteScanner:
             'color_profile': {},
             'alpha_bounding_box': content_bounds,
         }
+        analysis['category'] = self._resolve_category(analysis)
+        return analysis
+    
+    @staticmethod
+    def _resolve_category(analysis: Dict[str, Any]) -> str:
+        """Single label from the independent flags
+        
+        Priority is character > decoration > material > unknown; the
+        individual is_* flags are kept for backward compatibility.
+        """
+        if analysis['is_character']:
+            return 'character'
+        if analysis['is_decoration']:
+            return 'decoration'
+        if analysis['is_material']:
+            return 'material'
+        return 'unknown'
+    
+    @staticmethod
+    def _in_chest_range(r: int, g: int, b: int, band) -> bool:
+        """Check a color against one CHEST_COLOR_RANGES entry"""
+        (r_min, r_max), (g_min, g_max), b_max = band
+        return r_min <= r <= r_max and g_min <= g <= g_max and b <= b_max
+    
+    def chest_range_breakdown(self, pixels: bytes, width: int, height: int) -> Tuple[float, ...]:
+        """Opaque-pixel fraction matching each chest color range separately
+        
+        One entry per CHEST_COLOR_RANGES band, in order. A pixel in
+        overlapping bands counts toward each of them.
+        """
+        if len(pixels) != width * height * 4:
+            raise ValueError("Pixel data length doesn't match dimensions")
+        
+        matches = [0] * len(self.CHEST_COLOR_RANGES)
+        total_pixels = 0
+        for i in range(0, len(pixels), 4):
+            r, g, b, a = pixels[i:i + 4]
+            if a == 0:
+                continue
+            total_pixels += 1
+            for index, band in enumerate(self.CHEST_COLOR_RANGES):
+                if self._in_chest_range(r, g, b, band):
+                    matches[index] += 1
+        
+        return tuple(count / max(total_pixels, 1) for count in matches)
+    
+    def validate_analysis(self, pixels: bytes, width: int, height: int) -> List[str]:
+        """Run analyze_sprite and list internally inconsistent results
+        
+        Checks the reported bounds and transparency against the measured
+        opaque pixels, that the green/gray/brown-gold ratios sum to at most
+        1.0, and that is_material is not set together with is_character or
+        is_decoration. An empty list means the analysis is consistent.
+        """
+        if len(pixels) != width * height * 4:
+            raise ValueError("Pixel data length doesn't match dimensions")
+        
+        analysis = self.analyze_sprite(pixels, width, height)
+        warnings = []
+        
+        # Tight box and count of opaque pixels
+        opaque = [(i % width, i // width) for i, a in enumerate(pixels[3::4]) if a > 0]
+        if opaque:
+            xs = [x for x, _ in opaque]
+            ys = [y for _, y in opaque]
+            tight_bounds = (min(xs), min(ys), max(xs) - min(xs) + 1, max(ys) - min(ys) + 1)
+        else:
+            tight_bounds = (0, 0, 0, 0)
+        
+        _, _, bounds_width, bounds_height = analysis['content_bounds']
+        if bounds_width * bounds_height > tight_bounds[2] * tight_bounds[3]:
+            warnings.append(
+                f"content_bounds {analysis['content_bounds']} is larger than the "
+                f"opaque content {tight_bounds}"
+            )
+        
+        measured_transparency = 1.0 - len(opaque) / max(width * height, 1)
+        if abs(analysis['transparency_ratio'] - measured_transparency) > 1e-6:
+            warnings.append(
+                f"transparency_ratio {analysis['transparency_ratio']:.3f} differs from "
+                f"measured {measured_transparency:.3f}"
+            )
+        
+        ratio_sum = analysis['green_ratio'] + analysis['gray_ratio'] + analysis['brown_gold_ratio']
+        if ratio_sum > 1.0 + 1e-6:
+            warnings.append(f"green/gray/brown_gold ratios sum to {ratio_sum:.3f} (> 1.0)")
+        
+        if analysis['is_character'] and analysis['is_material']:
+            warnings.append("is_character and is_material are both set")
+        if analysis['is_decoration'] and analysis['is_material']:
+            warnings.append("is_decoration and is_material are both set")
+        
+        return warnings
     
     def auto_clean_edges(self, pixels: bytes, width: int, height: int, threshold: int) -> bytes:
         """Auto-clean sprite edges using Rust or Python"""
@@ -258,5 +392,6 @@ if __name__ == "__main__":
     print(f"  Is Character: {analysis['is_character']}")
     print(f"  Is Decoration: {analysis['is_decoration']}")
     print(f"  Is Material: {analysis['is_material']}")
+    print(f"  Category: {analysis['category']}")
     
     print("✅ Rust-Powered Scanner working!")
@@ -0,0 +1,512 @@
+"""
+Tests for the compiled dgt_harvest_rust Material Triage Engine
+Skipped when the Rust extension is not built
+"""
+
+import pytest
+
+dgt_harvest_rust = pytest.importorskip("dgt_harvest_rust")
+
+WHITE = (255, 255, 255, 255)
+BLACK = (0, 0, 0, 255)
+RED = (255, 0, 0, 255)
+
+
+def sprite_from_rows(rows, palette):
+    """Build RGBA bytes from equal-length strings mapping characters to colors"""
+    return bytes(channel for row in rows for char in row for channel in palette[char])
+
+
+def pixel(pixels, width, x, y):
+    """RGBA tuple at (x, y)"""
+    i = (y * width + x) * 4
+    return tuple(pixels[i:i + 4])
+
+
+class TestMaterialTriageEngine:
+    """Test suite for MaterialTriageEngine classification"""
+
+    @pytest.fixture
+    def engine(self):
+        """Fixture providing engine instance"""
+        return dgt_harvest_rust.MaterialTriageEngine()
+
+    def test_unclassifiable_sprite_is_unknown(self, engine):
+        """Test mostly "other" colors yield unknown material with low confidence"""
+        # 14 magenta (unclassifiable) + 2 grass pixels in a 4x4 sprite
+        pixels = bytes([255, 0, 255, 255] * 14 + [50, 150, 60, 255] * 2)
+
+        dna = engine.analyze_sprite(pixels, 4, 4)
+
+        assert dna.material_type == "unknown"
+        assert dna.confidence <= 0.3
+
+    def test_classified_sprite_keeps_confidence(self, engine):
+        """Test a clearly classified sprite is unaffected"""
+        pixels = bytes([50, 150, 60, 255] * 16)
+
+        dna = engine.analyze_sprite(pixels, 4, 4)
+
+        assert dna.material_type == "grass"
+        assert dna.confidence > 0.3
+
+    def test_overflowing_dimensions_raise(self, engine):
+        """Test u32::MAX dimensions raise instead of wrapping the length check"""
+        u32_max = 2**32 - 1
+
+        with pytest.raises(ValueError):
+            engine.analyze_sprite(b"", u32_max, u32_max)
+        with pytest.raises(ValueError):
+            engine.get_alpha_bounding_box(b"", u32_max, u32_max)
+
+    def test_u16_analysis_honours_max_dim(self):
+        """Test the 16-bit path downsamples like analyze_sprite"""
+        engine = dgt_harvest_rust.MaterialTriageEngine(analysis_max_dim=2)
+        pixels = bytearray(4 * 4 * 4)
+        for i in (5, 6):
+            pixels[i * 4:i * 4 + 4] = bytes([50, 150, 60, 255])
+        pixels_u16 = [value * 257 for value in pixels]
+
+        dna = engine.analyze_sprite(bytes(pixels), 4, 4)
+        dna_u16 = engine.analyze_sprite_u16(pixels_u16, 4, 4)
+
+        assert dna_u16.alpha_bounding_box == dna.alpha_bounding_box
+        assert dna_u16.material_type == dna.material_type
+
+    def test_flood_fill_stops_at_blocking_column(self, engine):
+        """Test a solid column of another color blocks the fill"""
+        rows = ["WWKWW"] * 3
+        pixels = sprite_from_rows(rows, {"W": WHITE, "K": BLACK})
+
+        filled = engine.flood_fill(pixels, 5, 3, 0, 1, RED, 0)
+
+        expected = sprite_from_rows(["RRKWW"] * 3, {"W": WHITE, "K": BLACK, "R": RED})
+        assert filled == expected
+
+    def test_flood_fill_follows_u_shaped_region(self, engine):
+        """Test spans seeded downward climb back up the other arm"""
+        rows = [
+            "WKKKW",
+            "WKKKW",
+            "WKKKW",
+            "WWWWW",
+        ]
+        pixels = sprite_from_rows(rows, {"W": WHITE, "K": BLACK})
+
+        filled = engine.flood_fill(pixels, 5, 4, 0, 0, RED, 0)
+
+        expected = [row.replace("W", "R") for row in rows]
+        assert filled == sprite_from_rows(expected, {"K": BLACK, "R": RED})
+
+    def test_flood_fill_is_four_connected(self, engine):
+        """Test pixels touching only diagonally are not filled"""
+        rows = ["WK", "KW"]
+        pixels = sprite_from_rows(rows, {"W": WHITE, "K": BLACK})
+
+        filled = engine.flood_fill(pixels, 2, 2, 0, 0, RED, 0)
+
+        assert pixel(filled, 2, 0, 0) == RED
+        assert pixel(filled, 2, 1, 1) == WHITE
+
+    def test_flood_fill_tolerance_is_inclusive(self, engine):
+        """Test a channel difference equal to tolerance matches, one more does not"""
+        seed = (100, 100, 100, 255)
+        near = (110, 100, 100, 255)
+        pixels = sprite_from_rows(["SN"], {"S": seed, "N": near})
+
+        assert pixel(engine.flood_fill(pixels, 2, 1, 0, 0, RED, 10), 2, 1, 0) == RED
+        assert pixel(engine.flood_fill(pixels, 2, 1, 0, 0, RED, 9), 2, 1, 0) == near
+
+    def test_flood_fill_rejects_seed_outside_image(self, engine):
+        """Test an out-of-bounds seed raises"""
+        pixels = sprite_from_rows(["WW"], {"W": WHITE})
+
+        with pytest.raises(ValueError):
+            engine.flood_fill(pixels, 2, 1, 2, 0, RED, 0)
+
+    def test_find_components_diagonal_line_connectivity(self, engine):
+        """Test a diagonal line splits under 4-connectivity and merges under 8"""
+        rows = ["X...", ".X..", "..X.", "...X"]
+        pixels = sprite_from_rows(rows, {"X": WHITE, ".": (0, 0, 0, 0)})
+
+        four = engine.find_components(pixels, 4, 4, connectivity=4)
+        eight = engine.find_components(pixels, 4, 4, connectivity=8)
+
+        assert four == [(i, i, 1, 1) for i in range(4)]
+        assert eight == [(0, 0, 4, 4)]
+
+    def test_find_components_min_area_and_invalid_connectivity(self, engine):
+        """Test small components are skipped and connectivity is validated"""
+        rows = ["XX.X", "XX.."]
+        pixels = sprite_from_rows(rows, {"X": WHITE, ".": (0, 0, 0, 0)})
+
+        assert engine.find_components(pixels, 4, 2, min_area=2) == [(0, 0, 2, 2)]
+        with pytest.raises(ValueError):
+            engine.find_components(pixels, 4, 2, connectivity=6)
+
+    def test_pack_atlas_wraps_shelves_with_padding(self, engine):
+        """Test rows wrap at max_width and neighbours are separated by padding"""
+        a = bytes(RED * 4)
+        b = bytes(WHITE * 6)
+        c = bytes(BLACK * 2)
+
+        atlas, width, height, placements = engine.pack_atlas(
+            [(a, 2, 2), (b, 3, 2), (c, 2, 1)], 6, 1
+        )
+
+        # a and b share the first shelf; c wraps below it after a padding row
+        assert placements == [(0, 0), (3, 0), (0, 3)]
+        assert (width, height) == (6, 4)
+        assert len(atlas) == width * height * 4
+        assert pixel(atlas, width, 1, 1) == RED
+        assert pixel(atlas, width, 2, 0) == (0, 0, 0, 0)
+        assert pixel(atlas, width, 3, 0) == WHITE
+        assert pixel(atlas, width, 0, 2) == (0, 0, 0, 0)
+        assert pixel(atlas, width, 1, 3) == BLACK
+
+    def test_pack_atlas_rejects_sprite_wider_than_atlas(self, engine):
+        """Test a sprite wider than max_width raises"""
+        with pytest.raises(ValueError):
+            engine.pack_atlas([(bytes(WHITE * 3), 3, 1)], 2, 0)
+
+    def test_count_holes_donut_versus_disc(self, engine):
+        """Test an enclosed gap is a hole while solid or open shapes have none"""
+        palette = {"X": WHITE, ".": (0, 0, 0, 0)}
+        donut = ["XXXXX", "X...X", "X...X", "X...X", "XXXXX"]
+        disc = [".XXX.", "XXXXX", "XXXXX", "XXXXX", ".XXX."]
+        # Gap opens onto the border, so it is background rather than a hole
+        open_ring = ["XXXXX", "X...X", "X...X", "X...X", "XX.XX"]
+
+        assert engine.count_holes(sprite_from_rows(donut, palette), 5, 5) == 1
+        assert engine.count_holes(sprite_from_rows(disc, palette), 5, 5) == 0
+        assert engine.count_holes(sprite_from_rows(open_ring, palette), 5, 5) == 0
+
+    def test_distance_transform_counts_outside_as_transparent(self, engine):
+        """Test border pixels are 1 and depth grows by Manhattan steps inward"""
+        pixels = sprite_from_rows(["XXXXX"] * 5, {"X": WHITE})
+
+        distances = engine.distance_transform(pixels, 5, 5)
+
+        assert distances == [
+            1, 1, 1, 1, 1,
+            1, 2, 2, 2, 1,
+            1, 2, 3, 2, 1,
+            1, 2, 2, 2, 1,
+            1, 1, 1, 1, 1,
+        ]
+
+    def test_distance_transform_transparent_pixels_are_zero(self, engine):
+        """Test transparent pixels are 0 and their neighbours 1"""
+        rows = ["XXX", "X.X", "XXX"]
+        pixels = sprite_from_rows(rows, {"X": WHITE, ".": (0, 0, 0, 0)})
+
+        assert engine.distance_transform(pixels, 3, 3) == [1, 1, 1, 1, 0, 1, 1, 1, 1]
+
+    def test_oversized_thumbnail_raises(self, engine):
+        """Test a max_dim whose RGBA buffer overflows raises instead of panicking"""
+        with pytest.raises(ValueError):
+            engine.thumbnail(bytes([10, 20, 30, 255]), 1, 1, 40000)
+
+    def test_oversized_legibility_target_raises(self, engine):
+        """Test small_size_legibility rejects targets whose buffer overflows"""
+        with pytest.raises(ValueError):
+            engine.small_size_legibility(bytes([10, 20, 30, 255]), 1, 1, 40000)
+
+    def test_full_frame_texture_is_tileable_background(self, engine):
+        """Test a seamless, opaque, low-detail texture routes as background"""
+        pixels = bytes([40, 90, 200, 255] * 256)
+
+        assert engine.is_tileable_background(pixels, 16, 16)
+
+    def test_isolated_item_is_not_tileable_background(self, engine):
+        """Test an item inside transparent margins routes as foreground"""
+        pixels = bytearray(16 * 16 * 4)
+        for y in range(4, 12):
+            for x in range(5, 11):
+                i = (y * 16 + x) * 4
+                pixels[i:i + 4] = bytes([180, 120, 60, 255])
+
+        assert not engine.is_tileable_background(bytes(pixels), 16, 16)
+
+    def test_asset_class_routes_by_size_and_fill(self, engine):
+        """Test asset_class separates tiles, icons and sprites"""
+        assert engine.asset_class(bytes([40, 90, 200, 255] * 256), 16, 16) == "tile"
+
+        icon = bytearray(24 * 24 * 4)
+        for y in range(4, 20):
+            for x in range(6, 18):
+                i = (y * 24 + x) * 4
+                icon[i:i + 4] = bytes([180, 120, 60, 255])
+        assert engine.asset_class(bytes(icon), 24, 24) == "icon"
+
+        # Same content in a tall 64x128 frame is too large for an icon
+        sprite = bytearray(64 * 128 * 4)
+        for y in range(4, 20):
+            for x in range(6, 18):
+                i = (y * 64 + x) * 4
+                sprite[i:i + 4] = bytes([180, 120, 60, 255])
+        assert engine.asset_class(bytes(sprite), 64, 128) == "sprite"
+
+    def test_luma_weights_drive_grayscale(self):
+        """Test custom luma_weights change grayscale-based methods"""
+        # Red and green differ in Rec.601 luma but match under equal weights
+        pixels = bytes([200, 0, 0, 255, 0, 200, 0, 255] * 8)
+
+        default = dgt_harvest_rust.MaterialTriageEngine()
+        equal = dgt_harvest_rust.MaterialTriageEngine(luma_weights=(1.0, 1.0, 1.0))
+
+        assert default.equalize(pixels, 4, 4) != pixels
+        assert equal.equalize(pixels, 4, 4) == pixels
+
+    def test_invalid_luma_weights_raise(self):
+        """Test weights without a positive sum are rejected"""
+        with pytest.raises(ValueError):
+            dgt_harvest_rust.MaterialTriageEngine(luma_weights=(0.0, 0.0, 0.0))
+        with pytest.raises(ValueError):
+            dgt_harvest_rust.MaterialTriageEngine(luma_weights=(-1.0, 1.0, 1.0))
+
+    def test_color_signature_two_by_two(self, engine):
+        """Test a 2x2 grid over a 2x2 sprite returns each pixel's color"""
+        pixels = bytes([10, 0, 0, 255, 20, 0, 0, 255, 30, 0, 0, 255, 40, 0, 0, 255])
+
+        signature = engine.color_signature(pixels, 2, 2, 2)
+
+        assert signature == [(10, 0, 0), (20, 0, 0), (30, 0, 0), (40, 0, 0)]
+
+    def test_color_signature_rejects_oversized_grid(self, engine):
+        """Test grids larger than the image raise instead of overflowing"""
+        pixels = bytes([10, 0, 0, 255] * 4)
+
+        with pytest.raises(ValueError):
+            engine.color_signature(pixels, 2, 2, 65536)
+        with pytest.raises(ValueError):
+            engine.color_signature(pixels, 2, 2, 0)
+
+    def test_capped_analysis_keeps_faint_pixels_in_bounds(self):
+        """Test downsampled analysis measures bounds on full-resolution alpha"""
+        engine = dgt_harvest_rust.MaterialTriageEngine(analysis_max_dim=4)
+        pixels = bytearray(64 * 64 * 4)
+        pixels[0:4] = bytes([200, 200, 200, 100])
+
+        # A lone faint pixel averages to alpha 0 at 4x4 but is still content
+        dna = engine.analyze_sprite(bytes(pixels), 64, 64)
+        assert dna.alpha_bounding_box == (0, 0, 1, 1)
+        assert not dna.is_empty
+
+        for y in range(16, 20):
+            for x in range(30, 34):
+                i = (y * 64 + x) * 4
+                pixels[i:i + 4] = bytes([50, 150, 60, 255])
+        dna = engine.analyze_sprite(bytes(pixels), 64, 64)
+        assert dna.alpha_bounding_box == (0, 0, 34, 20)
+
+    def test_rotate_quarter_turn_swaps_dimensions(self, engine):
+        """Test a 90 degree rotation turns a 3x2 sprite into 2x3"""
+        pixels = bytes([200, 100, 50, 255] * 6)
+
+        rotated, width, height = engine.rotate(pixels, 3, 2, 90.0)
+
+        assert (width, height) == (2, 3)
+        assert rotated == pixels
+
+    def test_rotate_rejects_oversized_canvas(self, engine):
+        """Test a canvas too large for RGBA indexing raises"""
+        with pytest.raises(ValueError):
+            engine.rotate(bytes(4 * 70000), 70000, 1, 45.0)
+
+    def test_blur_radius_zero_is_identity(self, engine):
+        """Test radius 0 returns the input unchanged for both modes"""
+        pixels = bytes([10, 200, 30, 255, 0, 0, 0, 0, 90, 80, 70, 128, 1, 2, 3, 4])
+
+        assert engine.blur(pixels, 2, 2, 0, "box") == pixels
+        assert engine.blur(pixels, 2, 2, 0, "gaussian") == pixels
+
+    def test_blur_keeps_edge_color_against_transparency(self, engine):
+        """Test premultiplied blurring fades alpha without darkening the color"""
+        # Opaque red pixel with transparent black neighbours
+        pixels = bytearray(3 * 3 * 4)
+        pixels[16:20] = bytes([255, 0, 0, 255])
+
+        for mode in ("box", "gaussian"):
+            blurred = engine.blur(bytes(pixels), 3, 3, 1, mode)
+            for i in range(9):
+                r, g, b, a = blurred[i * 4:i * 4 + 4]
+                assert 0 < a < 255
+                assert (r, g, b) == (255, 0, 0)
+
+    def test_blur_rejects_bad_mode_and_radius(self, engine):
+        """Test unknown modes and radii beyond the image raise"""
+        pixels = bytes([255, 0, 0, 255])
+
+        with pytest.raises(ValueError):
+            engine.blur(pixels, 1, 1, 1, "median")
+        with pytest.raises(ValueError):
+            engine.blur(pixels, 1, 1, 2**31, "gaussian")
+
+    def test_cache_hit_returns_stored_result(self, engine):
+        """Test repeated analysis of identical bytes is served from the cache"""
+        pixels = sprite_from_rows(["WW", "BW"], {"W": WHITE, "B": BLACK})
+
+        assert engine.cache_stats() is None
+        engine.enable_cache(4)
+        first = engine.analyze_sprite(pixels, 2, 2)
+        second = engine.analyze_sprite(pixels, 2, 2)
+
+        assert engine.cache_stats() == (1, 1, 1)
+        assert second.alpha_bounding_box == first.alpha_bounding_box
+        assert second.dominant_color == first.dominant_color
+
+    def test_cache_evicts_least_recently_used(self, engine):
+        """Test a hit refreshes an entry so the oldest unused one is evicted"""
+        a = sprite_from_rows(["W"], {"W": WHITE})
+        b = sprite_from_rows(["B"], {"B": BLACK})
+        c = sprite_from_rows(["R"], {"R": RED})
+
+        engine.enable_cache(2)
+        engine.analyze_sprite(a, 1, 1)
+        engine.analyze_sprite(b, 1, 1)
+        engine.analyze_sprite(a, 1, 1)  # Hit, b is now the oldest
+        engine.analyze_sprite(c, 1, 1)  # Evicts b
+        assert engine.cache_stats() == (1, 3, 2)
+
+        engine.analyze_sprite(a, 1, 1)
+        assert engine.cache_stats() == (2, 3, 2)
+        engine.analyze_sprite(b, 1, 1)
+        assert engine.cache_stats() == (2, 4, 2)
+
+    def test_cache_rejects_zero_capacity(self, engine):
+        """Test a zero-capacity cache is rejected"""
+        with pytest.raises(ValueError):
+            engine.enable_cache(0)
+
+    def test_clear_cache_drops_entries(self, engine):
+        """Test clear_cache empties the cache but keeps it enabled"""
+        pixels = sprite_from_rows(["W"], {"W": WHITE})
+
+        engine.enable_cache(2)
+        engine.analyze_sprite(pixels, 1, 1)
+        engine.clear_cache()
+        assert engine.cache_stats() == (0, 1, 0)
+
+        engine.analyze_sprite(pixels, 1, 1)
+        assert engine.cache_stats() == (0, 2, 1)
+
+    def test_analyze_sprite_buffer_accepts_buffer_objects(self, engine):
+        """Test bytearray and memoryview input match plain bytes"""
+        pixels = sprite_from_rows(["RW", "WB"], {"R": RED, "W": WHITE, "B": BLACK})
+        expected = engine.analyze_sprite(pixels, 2, 2)
+
+        for buffer in (bytearray(pixels), memoryview(pixels), memoryview(bytearray(pixels))):
+            dna = engine.analyze_sprite_buffer(buffer, 2, 2)
+            assert dna.alpha_bounding_box == expected.alpha_bounding_box
+            assert dna.dominant_color == expected.dominant_color
+
+    def test_analyze_sprite_buffer_rejects_bad_buffers(self, engine):
+        """Test non-contiguous views and wrong lengths raise ValueError"""
+        doubled = sprite_from_rows(["RRWW"], {"R": RED, "W": WHITE})
+
+        with pytest.raises(ValueError):
+            engine.analyze_sprite_buffer(memoryview(doubled)[::2], 2, 1)
+        with pytest.raises(ValueError):
+            engine.analyze_sprite_buffer(bytearray(doubled), 1, 1)
+
+    def test_set_thread_count_keeps_results(self):
+        """Test parallel work gives the same answer in a sized pool and the default pool"""
+        prev = sprite_from_rows(["WWWW"], {"W": WHITE})
+        curr = sprite_from_rows(["WBWB"], {"W": WHITE, "B": BLACK})
+
+        try:
+            dgt_harvest_rust.set_thread_count(2)
+            pooled = dgt_harvest_rust.compare_frames(prev, curr, 4, 1, 0)
+        finally:
+            dgt_harvest_rust.set_thread_count(0)
+
+        assert pooled == 0.5
+        assert dgt_harvest_rust.compare_frames(prev, curr, 4, 1, 0) == pooled
+
+    def test_material_dna_bytes_round_trip(self, engine):
+        """Test to_bytes/from_bytes restores every field"""
+        pixels = sprite_from_rows(["RW.", "WB."], {"R": RED, "W": WHITE, "B": BLACK, ".": (0, 0, 0, 0)})
+        dna = engine.analyze_sprite(pixels, 3, 2)
+
+        restored = dgt_harvest_rust.MaterialDNA.from_bytes(dna.to_bytes())
+
+        for field in ("alpha_bounding_box", "material_type", "confidence", "color_profile",
+                      "edge_density", "is_object", "dominant_color", "transparency_ratio"):
+            assert getattr(restored, field) == getattr(dna, field)
+
+    def test_material_dna_from_bytes_rejects_malformed_input(self, engine):
+        """Test empty and truncated encodings raise ValueError"""
+        encoded = engine.analyze_sprite(sprite_from_rows(["W"], {"W": WHITE}), 1, 1).to_bytes()
+
+        with pytest.raises(ValueError):
+            dgt_harvest_rust.MaterialDNA.from_bytes(b"")
+        with pytest.raises(ValueError):
+            dgt_harvest_rust.MaterialDNA.from_bytes(encoded[:len(encoded) // 2])
+
+    def test_equalize_preserves_chroma(self, engine):
+        """Test equalization shifts luma while keeping channel differences"""
+        pixels = bytes([10, 10, 10, 255, 150, 100, 100, 255, 240, 240, 240, 255])
+
+        equalized = engine.equalize(pixels, 3, 1)
+
+        assert equalized[0:4] == bytes([0, 0, 0, 255])
+        assert equalized[8:12] == bytes([255, 255, 255, 255])
+        r, g, b, a = equalized[4:8]
+        assert r > 150 and a == 255
+        assert (r - g, g - b) == (50, 0)
+
+    def test_drop_shadow_grows_canvas(self, engine):
+        """Test the canvas grows to hold the offset and blurred shadow"""
+        pixels = sprite_from_rows(["RR", "RR"], {"R": RED})
+
+        _, width, height = engine.add_drop_shadow(pixels, 2, 2, 3, 1, 128, 1)
+        assert (width, height) == (6, 4)
+
+        # A negative offset grows the canvas left and shifts the sprite right
+        shadowed, width, height = engine.add_drop_shadow(pixels, 2, 2, -2, 0, 128, 0)
+        assert (width, height) == (4, 2)
+        assert pixel(shadowed, width, 0, 0) == (0, 0, 0, 128)
+        assert pixel(shadowed, width, 2, 0) == RED
+
+    def test_auto_upright_folds_angle(self, engine):
+        """Test the applied rotation takes the shorter way to vertical"""
+        white = {"#": WHITE, ".": (0, 0, 0, 0)}
+        vertical = sprite_from_rows(["#"] * 5, white)
+        diagonal = sprite_from_rows(["#....", ".#...", "..#..", "...#.", "....#"], white)
+        anti_diagonal = sprite_from_rows(["....#", "...#.", "..#..", ".#...", "#...."], white)
+
+        assert engine.auto_upright(vertical, 1, 5) == (vertical, 1, 5, 0.0)
+        assert engine.auto_upright(sprite_from_rows(["#####"], white), 5, 1)[1:] == (1, 5, 90.0)
+        assert engine.auto_upright(diagonal, 5, 5)[3] == -45.0
+        assert engine.auto_upright(anti_diagonal, 5, 5)[3] == 45.0
+
+    def test_defringe_bleeds_opaque_color(self, engine):
+        """Test soft edges take the nearest opaque color and keep their alpha"""
+        pixels = bytes([255, 0, 0, 255, 0, 0, 0, 128, 0, 0, 0, 0, 9, 9, 9, 64])
+
+        defringed = engine.defringe(pixels, 4, 1)
+
+        assert defringed[0:4] == bytes([255, 0, 0, 255])
+        assert defringed[4:8] == bytes([255, 0, 0, 128])
+        assert defringed[8:12] == bytes([0, 0, 0, 0])
+        # Cut off from opaque pixels by the transparent one
+        assert defringed[12:16] == bytes([9, 9, 9, 64])
+
+    def test_fit_to_size_centers_content(self, engine):
+        """Test content is centered when padding and cropped evenly when larger"""
+        clear = (0, 0, 0, 0)
+        corner = sprite_from_rows(["..", ".R"], {"R": RED, ".": clear})
+
+        padded = engine.fit_to_size(corner, 2, 2, 3, 3)
+        assert len(padded) == 3 * 3 * 4
+        assert [pixel(padded, 3, x, y) for y in range(3) for x in range(3)].count(RED) == 1
+        assert pixel(padded, 3, 1, 1) == RED
+
+        row = sprite_from_rows(["RWBW"], {"R": RED, "W": WHITE, "B": BLACK})
+        cropped = engine.fit_to_size(row, 4, 1, 2, 1)
+        assert (pixel(cropped, 2, 0, 0), pixel(cropped, 2, 1, 0)) == (WHITE, BLACK)
+
+
+if __name__ == "__main__":
+    pytest.main([__file__, "-v"])
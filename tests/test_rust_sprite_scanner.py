@@ -45,6 +45,10 @@ class TestRustSpriteScanner:
         assert scanner.green_threshold == 0.2
         assert scanner.gray_threshold == 0.3
         assert scanner.diversity_threshold == 0.05
+        assert scanner.character_min_pixels == 20
+        assert scanner.character_aspect_range == (0.5, 2.0)
+        assert scanner.character_min_colors == 3
+        assert scanner.compute_diversity is True
         
         # Rust engine availability depends on build
         if RUST_AVAILABLE:
@@ -63,7 +67,7 @@ class TestRustSpriteScanner:
             'green_ratio', 'gray_ratio', 'brown_gold_ratio', 'is_character',
             'is_decoration', 'is_material', 'material_type', 'confidence',
             'edge_density', 'is_object', 'dominant_color', 'transparency_ratio',
-            'color_profile', 'alpha_bounding_box'
+            'color_profile', 'alpha_bounding_box', 'category'
         }
         assert set(analysis.keys()) == required_keys
         
@@ -89,6 +93,7 @@ class TestRustSpriteScanner:
         assert isinstance(analysis['color_profile'], dict)
         assert isinstance(analysis['alpha_bounding_box'], tuple)
         assert len(analysis['alpha_bounding_box']) == 4
+        assert analysis['category'] in {'character', 'decoration', 'material', 'unknown'}
     
     def test_analyze_green_sprite(self, scanner: RustSpriteScanner, test_pixels_green: bytes):
         """Test analysis of green (grass-like) sprite"""
@@ -125,6 +130,95 @@ class TestRustSpriteScanner:
         # Lower threshold should detect more chests
         assert low_is_chest >= default_is_chest
     
+    def test_character_min_pixels(self):
+        """Test is_character honours the configured minimum pixel count"""
+        # 16x16 sprite cycling through four distinct colors
+        palette = [[200, 40, 40, 255], [40, 200, 40, 255], [40, 40, 200, 255], [200, 200, 40, 255]]
+        pixels = bytes(sum((palette[i % 4] for i in range(256)), []))
+        
+        scanner = RustSpriteScanner(character_min_colors=2)
+        scanner.rust_engine = None
+        assert scanner.analyze_sprite(pixels, 16, 16)['is_character']
+        
+        strict = RustSpriteScanner(character_min_pixels=256, character_min_colors=2)
+        strict.rust_engine = None
+        assert not strict.analyze_sprite(pixels, 16, 16)['is_character']
+    
+    def test_skip_color_diversity(self, test_pixels_green: bytes):
+        """Test compute_diversity=False reports zero diversity"""
+        scanner = RustSpriteScanner(compute_diversity=False)
+        scanner.rust_engine = None
+        analysis = scanner.analyze_sprite(test_pixels_green, 16, 16)
+        
+        assert analysis['color_diversity'] == 0.0
+        assert not analysis['is_material']
+        # Ratio signals still drive decoration detection
+        assert analysis['is_decoration']
+        assert not analysis['is_character']
+        assert analysis['category'] == 'decoration'
+    
+    def test_skip_color_diversity_never_reports_character(self, test_pixels_16x16: bytes):
+        """Test is_character stays False when the color count is skipped"""
+        scanner = RustSpriteScanner(compute_diversity=False, character_min_colors=0)
+        scanner.rust_engine = None
+        analysis = scanner.analyze_sprite(test_pixels_16x16, 16, 16)
+        
+        assert not analysis['is_character']
+        assert not analysis['is_object']
+        assert analysis['category'] == 'unknown'
+    
+    def test_chest_range_breakdown(self, scanner: RustSpriteScanner, test_pixels_16x16: bytes):
+        """Test per-range chest fractions"""
+        breakdown = scanner.chest_range_breakdown(test_pixels_16x16, 16, 16)
+        
+        # One fraction per configured range; brown pixels only hit the wood band
+        assert len(breakdown) == len(RustSpriteScanner.CHEST_COLOR_RANGES)
+        assert breakdown[0] == 1.0
+        assert breakdown[1] == 0.0
+    
+    def test_category_resolves_overlapping_flags(self, test_pixels_green: bytes):
+        """Test category picks one label when several flags are set"""
+        scanner = RustSpriteScanner()
+        scanner.rust_engine = None
+        
+        # Flat green is both decoration (green ratio) and material (one color)
+        analysis = scanner.analyze_sprite(test_pixels_green, 16, 16)
+        assert analysis['is_decoration'] and analysis['is_material']
+        assert analysis['category'] == 'decoration'
+        
+        # Four-color, green-heavy sprite is a character and a decoration
+        palette = [[40, 200, 40, 255], [60, 220, 50, 255], [30, 180, 60, 255], [200, 40, 40, 255]]
+        pixels = bytes(sum((palette[i % 4] for i in range(256)), []))
+        scanner.character_min_colors = 2
+        analysis = scanner.analyze_sprite(pixels, 16, 16)
+        assert analysis['is_character'] and analysis['is_decoration']
+        assert analysis['category'] == 'character'
+        
+        # Nothing set resolves to unknown
+        scanner.compute_diversity = False
+        analysis = scanner.analyze_sprite(bytes(16 * 16 * 4), 16, 16)
+        assert analysis['category'] == 'unknown'
+    
+    def test_validate_analysis(self, test_pixels_green: bytes):
+        """Test validate_analysis flags inconsistent fallback results"""
+        scanner = RustSpriteScanner()
+        scanner.rust_engine = None
+        
+        # A single flat green fills the frame: bounds and transparency agree,
+        # but one color flags both decoration (green) and material (low diversity)
+        warnings = scanner.validate_analysis(test_pixels_green, 16, 16)
+        assert warnings == ["is_decoration and is_material are both set"]
+        
+        # Fallback reports full-frame bounds and no transparency for a small item
+        pixels = bytearray(16 * 16 * 4)
+        pixels[0:4] = bytes([200, 40, 40, 255])
+        warnings = scanner.validate_analysis(bytes(pixels), 16, 16)
+        assert any("content_bounds" in warning for warning in warnings)
+        assert any("transparency_ratio" in warning for warning in warnings)
+        
+        with pytest.raises(ValueError):
+            scanner.validate_analysis(bytes(10), 16, 16)
+    
     def test_edge_cleaning(self, scanner: RustSpriteScanner, test_pixels_16x16: bytes):
         """Test sprite edge cleaning"""
         cleaned = scanner.auto_clean_edges(test_pixels_16x16, 16, 16, 2)